@@ -0,0 +1,165 @@
+//Huffman adaptativo (algoritmo FGK). a arvore começa só com o nó NYT ("not
+//yet transmitted") e é atualizada a cada simbolo, do mesmo jeito no
+//compactador e no descompactador, então nenhuma tabela vai no cabeçalho e a
+//saida pode começar antes de a entrada terminar.
+//
+//um simbolo novo é escrito como o código do NYT seguido do simbolo cru
+//(8 bits no alfabeto de bytes, 21 no de chars)
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::huffman::Alphabet;
+
+const NENHUM: usize = usize::MAX;
+
+#[derive(Debug, Clone)]
+struct No {
+    peso: u64,
+    pai: usize,
+    //filhos só existem nos nós internos
+    esq: usize,
+    dir: usize,
+    //simbolo das folhas; o NYT é a folha sem simbolo
+    sym: Option<u32>,
+}
+
+//a posição no vetor é a ordem do nó: indice 0 é a raiz e os pesos nunca
+//crescem conforme o indice aumenta (propriedade dos irmãos)
+#[derive(Debug, Clone)]
+pub struct AdaptiveModel {
+    nos: Vec<No>,
+    folhas: HashMap<u32, usize>,
+    nyt: usize,
+    bits_cru: u32,
+}
+
+impl AdaptiveModel {
+    pub fn new(alphabet: Alphabet) -> Self {
+        AdaptiveModel {
+            nos: vec![No { peso: 0, pai: NENHUM, esq: NENHUM, dir: NENHUM, sym: None }],
+            folhas: HashMap::new(),
+            nyt: 0,
+            bits_cru: match alphabet {
+                Alphabet::Bytes => 8,
+                Alphabet::Chars => 21,
+            },
+        }
+    }
+
+    //caminho da raiz até o nó, como (código, tamanho)
+    fn codigo(&self, mut no: usize) -> (u64, u32) {
+        let mut code = 0u64;
+        let mut len = 0u32;
+        while self.nos[no].pai != NENHUM {
+            let pai = self.nos[no].pai;
+            if self.nos[pai].dir == no {
+                code |= 1 << len;
+            }
+            len += 1;
+            no = pai;
+        }
+        (code, len)
+    }
+
+    //troca de lugar as subarvores que estão nas posições a e b
+    fn trocar(&mut self, a: usize, b: usize) {
+        let (pai_a, pai_b) = (self.nos[a].pai, self.nos[b].pai);
+        self.nos.swap(a, b);
+        self.nos[a].pai = pai_a;
+        self.nos[b].pai = pai_b;
+        for pos in [a, b] {
+            let No { esq, dir, sym, .. } = self.nos[pos];
+            if esq != NENHUM {
+                self.nos[esq].pai = pos;
+                self.nos[dir].pai = pos;
+            }
+            match sym {
+                Some(s) => {
+                    self.folhas.insert(s, pos);
+                }
+                None if esq == NENHUM => self.nyt = pos,
+                None => {}
+            }
+        }
+    }
+
+    //conta mais uma ocorrencia do simbolo e reorganiza a arvore
+    pub fn update(&mut self, sym: u32) {
+        let mut no = match self.folhas.get(&sym) {
+            Some(&folha) => folha,
+            None => {
+                //o NYT vira um nó interno com um novo NYT à esquerda e a
+                //folha do simbolo à direita, ambos com peso 0
+                let antigo = self.nyt;
+                let folha = self.nos.len();
+                let nyt = folha + 1;
+                self.nos.push(No { peso: 0, pai: antigo, esq: NENHUM, dir: NENHUM, sym: Some(sym) });
+                self.nos.push(No { peso: 0, pai: antigo, esq: NENHUM, dir: NENHUM, sym: None });
+                self.nos[antigo].esq = nyt;
+                self.nos[antigo].dir = folha;
+                self.folhas.insert(sym, folha);
+                self.nyt = nyt;
+                folha
+            }
+        };
+        loop {
+            //lider do bloco: o nó de menor indice com o mesmo peso
+            let peso = self.nos[no].peso;
+            let mut lider = no;
+            while lider > 0 && self.nos[lider - 1].peso == peso {
+                lider -= 1;
+            }
+            if lider != no && lider != self.nos[no].pai {
+                self.trocar(lider, no);
+                no = lider;
+            }
+            self.nos[no].peso += 1;
+            if self.nos[no].pai == NENHUM {
+                break;
+            }
+            no = self.nos[no].pai;
+        }
+    }
+
+    pub fn encode<W: Write>(&mut self, sym: u32, bits: &mut BitWriter<W>) -> io::Result<()> {
+        match self.folhas.get(&sym) {
+            Some(&folha) => {
+                let (code, len) = self.codigo(folha);
+                bits.write_bits(code, len)?;
+            }
+            None => {
+                let (code, len) = self.codigo(self.nyt);
+                bits.write_bits(code, len)?;
+                bits.write_bits(sym as u64, self.bits_cru)?;
+            }
+        }
+        self.update(sym);
+        Ok(())
+    }
+
+    //None se os dados acabarem no meio de um simbolo
+    pub fn decode<R: Read>(&mut self, bits: &mut BitReader<R>) -> io::Result<Option<u32>> {
+        let mut no = 0;
+        while self.nos[no].esq != NENHUM {
+            no = match bits.read_bit()? {
+                Some(false) => self.nos[no].esq,
+                Some(true) => self.nos[no].dir,
+                None => return Ok(None),
+            };
+        }
+        let sym = match self.nos[no].sym {
+            Some(sym) => sym,
+            None => {
+                let (v, disponiveis) = bits.peek(self.bits_cru)?;
+                if disponiveis < self.bits_cru {
+                    return Ok(None);
+                }
+                bits.consume(self.bits_cru);
+                v as u32
+            }
+        };
+        self.update(sym);
+        Ok(Some(sym))
+    }
+}
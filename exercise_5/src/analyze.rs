@@ -0,0 +1,165 @@
+//relatorio de estatisticas para decidir se o Huffman compensa: histograma,
+//entropia de Shannon, tamanho medio dos códigos de Huffman de ordem 0
+//(limitados e canonicos, como no .huff), tabela de códigos, overhead do
+//cabeçalho e taxa final.
+//
+//a entrada pode ser um arquivo qualquer ou um .huff. no arquivo qualquer o
+//tamanho e o overhead são os do .huff de duas passadas (uma tabela só) que
+//o compress --duas-passadas gravaria; num .huff são os do proprio arquivo,
+//e o histograma é o dos dados descompactados
+use std::fmt::Write as _;
+use std::io::Cursor;
+
+use crate::container::{self, MAGIC};
+use crate::error::HuffmanError;
+use crate::huffman::{
+    canonical_codes, contar_frequencias, limited_code_lengths, Alphabet, DEFAULT_MAX_CODE_LEN,
+};
+use crate::stream::{self, Options};
+use crate::Decoder;
+
+//uma linha da tabela de códigos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolStats {
+    pub symbol: u32,
+    pub count: u64,
+    //código canonico com o limite de DEFAULT_MAX_CODE_LEN bits, o mesmo que
+    //o compress grava, como '0's e '1's. vazio quando a entrada tem um
+    //simbolo só
+    pub code: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub alphabet: Alphabet,
+    //a entrada já era um .huff
+    pub from_huff: bool,
+    pub symbols: u64,
+    //bits por simbolo
+    pub entropy: f64,
+    pub average_code_len: f64,
+    //em ordem decrescente de contagem, desempatando pelo simbolo
+    pub table: Vec<SymbolStats>,
+    pub original_len: u64,
+    pub compressed_len: u64,
+    //bytes que não são payload: cabeçalho, tabelas, trailer
+    pub header_overhead: u64,
+}
+
+impl Analysis {
+    //compactado / original (0 com a entrada vazia)
+    pub fn ratio(&self) -> f64 {
+        if self.original_len == 0 {
+            0.0
+        } else {
+            self.compressed_len as f64 / self.original_len as f64
+        }
+    }
+
+    //o mesmo relatorio em JSON, para dashboards
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let alfabeto = match self.alphabet {
+            Alphabet::Bytes => "bytes",
+            Alphabet::Chars => "chars",
+        };
+        let _ = write!(
+            out,
+            "{{\"alphabet\":\"{}\",\"from_huff\":{},\"symbols\":{},\"distinct\":{},\
+             \"entropy\":{:.6},\"average_code_len\":{:.6},\"original_len\":{},\
+             \"compressed_len\":{},\"header_overhead\":{},\"ratio\":{:.6},\"table\":[",
+            alfabeto,
+            self.from_huff,
+            self.symbols,
+            self.table.len(),
+            self.entropy,
+            self.average_code_len,
+            self.original_len,
+            self.compressed_len,
+            self.header_overhead,
+            self.ratio(),
+        );
+        for (i, linha) in self.table.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{{\"symbol\":{},\"count\":{},\"length\":{},\"code\":\"{}\"}}",
+                linha.symbol,
+                linha.count,
+                linha.code.len(),
+                linha.code
+            );
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+//histograma, entropia e códigos dos simbolos de `data`
+fn estatisticas(data: &[u8], alphabet: Alphabet) -> Result<Analysis, HuffmanError> {
+    let simbolos = alphabet.symbols(data)?;
+    let freqs = contar_frequencias(&simbolos);
+    let codes = canonical_codes(&limited_code_lengths(&freqs, DEFAULT_MAX_CODE_LEN));
+    let total = simbolos.len() as u64;
+    let mut table: Vec<SymbolStats> = freqs
+        .iter()
+        .map(|(&symbol, &count)| SymbolStats {
+            symbol,
+            count,
+            code: match codes[&symbol] {
+                (_, 0) => String::new(),
+                (code, len) => format!("{:0width$b}", code, width = len as usize),
+            },
+        })
+        .collect();
+    table.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.symbol.cmp(&b.symbol)));
+
+    let (mut entropy, mut bits) = (0.0, 0u64);
+    for linha in &table {
+        let p = linha.count as f64 / total as f64;
+        entropy -= p * p.log2();
+        bits += linha.count * linha.code.len() as u64;
+    }
+    Ok(Analysis {
+        alphabet,
+        from_huff: false,
+        symbols: total,
+        entropy,
+        average_code_len: if total == 0 { 0.0 } else { bits as f64 / total as f64 },
+        table,
+        original_len: data.len() as u64,
+        compressed_len: 0,
+        header_overhead: 0,
+    })
+}
+
+//analisa um arquivo qualquer (com o alfabeto dado) ou um .huff (com o
+//alfabeto gravado nele)
+pub fn analyze(data: &[u8], alphabet: Alphabet) -> Result<Analysis, HuffmanError> {
+    analyze_with(data, alphabet, &Decoder::new())
+}
+
+//como analyze, descompactando o .huff com `decoder`: um .huff do método
+//Dictionary precisa de um Decoder com o dicionario dele
+pub fn analyze_with(
+    data: &[u8],
+    alphabet: Alphabet,
+    decoder: &Decoder,
+) -> Result<Analysis, HuffmanError> {
+    let (mut analysis, info) = if data.starts_with(&MAGIC) {
+        let info = container::info(data)?;
+        let alphabet = info.header.ok_or(HuffmanError::UnknownFormat)?.alphabet;
+        (estatisticas(&decoder.decompress_bytes(data)?, alphabet)?, info)
+    } else {
+        let mut arquivo = Vec::new();
+        let options = Options { alphabet, ..Default::default() };
+        stream::compress_seekable(Cursor::new(data), &mut arquivo, &options)?;
+        (estatisticas(data, alphabet)?, container::info(arquivo.as_slice())?)
+    };
+    analysis.from_huff = data.starts_with(&MAGIC);
+    analysis.compressed_len = info.compressed_len;
+    analysis.header_overhead = info.compressed_len - info.payload_len;
+    Ok(analysis)
+}
@@ -0,0 +1,397 @@
+//arquivo de arquivos (parecido com o tar): junta varios arquivos e
+//diretorios num só, cada arquivo compactado separadamente.
+//
+//formato:
+//  0..4   magic b"HUA\x1a"
+//  4      versão (1)
+//  entradas, cada uma:
+//    varint  tamanho do caminho em bytes (0 marca o fim do arquivo)
+//            caminho relativo em UTF-8, com '/' entre os componentes
+//    u8      tipo: 0 = arquivo, 1 = diretorio
+//    u32     permissões Unix (mode & 0o7777), big-endian
+//    i64     mtime em segundos desde 1970, big-endian
+//    u64     tamanho original, big-endian
+//    u64     tamanho dos dados compactados, big-endian (0 nos diretorios)
+//            dados: um .huff completo, com cabeçalho, blocos e CRC
+//
+//como cada entrada diz quanto ocupa, listar e extrair um membro só pula os
+//dados dos outros sem decodificar. links simbolicos não são guardados.
+//setuid, setgid e sticky ficam gravados, mas a extração só os aplica se
+//pedido (extract_all_with)
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::HuffmanError;
+use crate::huffman::{read_varint, write_varint};
+use crate::stream::Options;
+use crate::{Decoder, Encoder};
+
+pub const MAGIC: [u8; 4] = *b"HUA\x1a";
+pub const VERSION: u8 = 1;
+const MAX_PATH: u64 = 4096;
+//tipo, permissões, mtime e os dois tamanhos
+const CAMPOS_FIXOS: usize = 1 + 4 + 8 + 8 + 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Dir,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub kind: EntryKind,
+    pub mode: u32,
+    pub mtime: i64,
+    pub size: u64,
+    pub compressed_size: u64,
+}
+
+fn escrever_entrada<W: Write>(out: &mut W, entry: &Entry) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(entry.path.len() + CAMPOS_FIXOS + 2);
+    write_varint(entry.path.len() as u64, &mut buf);
+    buf.extend_from_slice(entry.path.as_bytes());
+    buf.push(match entry.kind {
+        EntryKind::File => 0,
+        EntryKind::Dir => 1,
+    });
+    buf.extend_from_slice(&entry.mode.to_be_bytes());
+    buf.extend_from_slice(&entry.mtime.to_be_bytes());
+    buf.extend_from_slice(&entry.size.to_be_bytes());
+    buf.extend_from_slice(&entry.compressed_size.to_be_bytes());
+    out.write_all(&buf)
+}
+
+//lê a proxima entrada; None no marcador de fim
+fn ler_entrada<R: Read>(input: &mut R) -> Result<Option<Entry>, HuffmanError> {
+    let len = read_varint(input)?;
+    if len == 0 {
+        return Ok(None);
+    }
+    if len > MAX_PATH {
+        return Err(HuffmanError::Corrupt("caminho longo demais"));
+    }
+    let mut path = vec![0u8; len as usize];
+    input.read_exact(&mut path)?;
+    let path = String::from_utf8(path).map_err(|_| HuffmanError::Corrupt("caminho não é UTF-8"))?;
+    let mut campos = [0u8; CAMPOS_FIXOS];
+    input.read_exact(&mut campos)?;
+    let kind = match campos[0] {
+        0 => EntryKind::File,
+        1 => EntryKind::Dir,
+        _ => return Err(HuffmanError::Corrupt("tipo de entrada desconhecido")),
+    };
+    let u64_em = |i: usize| u64::from_be_bytes(campos[i..i + 8].try_into().unwrap());
+    Ok(Some(Entry {
+        path,
+        kind,
+        mode: u32::from_be_bytes(campos[1..5].try_into().unwrap()),
+        mtime: u64_em(5) as i64,
+        size: u64_em(13),
+        compressed_size: u64_em(21),
+    }))
+}
+
+fn ler_magic<R: Read>(input: &mut R) -> Result<(), HuffmanError> {
+    let mut cabecalho = [0u8; 5];
+    input.read_exact(&mut cabecalho).map_err(|_| HuffmanError::UnknownFormat)?;
+    if cabecalho[..4] != MAGIC {
+        return Err(HuffmanError::UnknownFormat);
+    }
+    if cabecalho[4] != VERSION {
+        return Err(HuffmanError::UnsupportedVersion(cabecalho[4]));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn permissoes(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn permissoes(meta: &fs::Metadata) -> u32 {
+    if meta.permissions().readonly() {
+        0o444
+    } else if meta.is_dir() {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+fn mtime(meta: &fs::Metadata) -> i64 {
+    match meta.modified().map(|t| t.duration_since(UNIX_EPOCH)) {
+        Ok(Ok(d)) => d.as_secs() as i64,
+        Ok(Err(antes)) => -(antes.duration().as_secs() as i64),
+        Err(_) => 0,
+    }
+}
+
+//caminho relativo a partir dos componentes, sempre com '/'
+fn nome(componentes: &[String]) -> String {
+    componentes.join("/")
+}
+
+//junta um caminho ao arquivo, descendo nos diretorios em ordem alfabetica
+fn adicionar<W: Write + Seek>(
+    out: &mut W,
+    caminho: &Path,
+    componentes: &mut Vec<String>,
+    encoder: &Encoder,
+    entradas: &mut Vec<Entry>,
+) -> Result<(), HuffmanError> {
+    let meta = fs::symlink_metadata(caminho)?;
+    let mut entry = Entry {
+        path: nome(componentes),
+        kind: EntryKind::File,
+        mode: permissoes(&meta),
+        mtime: mtime(&meta),
+        size: 0,
+        compressed_size: 0,
+    };
+    if meta.is_dir() {
+        entry.kind = EntryKind::Dir;
+        escrever_entrada(out, &entry)?;
+        entradas.push(entry);
+        adicionar_filhos(out, caminho, componentes, encoder, entradas)?;
+    } else if meta.is_file() {
+        //o tamanho compactado só é conhecido no fim: grava a entrada, os
+        //dados, e volta para corrigir
+        let inicio = out.stream_position()?;
+        escrever_entrada(out, &entry)?;
+        let dados = out.stream_position()?;
+        entry.size = encoder.compress(File::open(caminho)?, &mut *out)?;
+        let fim = out.stream_position()?;
+        entry.compressed_size = fim - dados;
+        out.seek(SeekFrom::Start(inicio))?;
+        escrever_entrada(out, &entry)?;
+        out.seek(SeekFrom::Start(fim))?;
+        entradas.push(entry);
+    }
+    Ok(())
+}
+
+//o conteudo de um diretorio, em ordem alfabetica, abaixo de `componentes`
+fn adicionar_filhos<W: Write + Seek>(
+    out: &mut W,
+    caminho: &Path,
+    componentes: &mut Vec<String>,
+    encoder: &Encoder,
+    entradas: &mut Vec<Entry>,
+) -> Result<(), HuffmanError> {
+    let mut filhos: Vec<_> = fs::read_dir(caminho)?.collect::<io::Result<_>>()?;
+    filhos.sort_by_key(|f| f.file_name());
+    for filho in filhos {
+        let nome = filho.file_name().into_string().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "nome de arquivo não é UTF-8")
+        })?;
+        componentes.push(nome);
+        adicionar(out, &filho.path(), componentes, encoder, entradas)?;
+        componentes.pop();
+    }
+    Ok(())
+}
+
+//cria um arquivo com os caminhos dados. cada um entra com o seu proprio
+//nome (o ultimo componente) e os diretorios são percorridos recursivamente.
+//um diretorio sem nome ("." ou "..", como no tar c .) entra só com o
+//conteudo, cada filho com o nome dele. devolve as entradas gravadas
+pub fn create<W: Write + Seek, P: AsRef<Path>>(
+    output: W,
+    paths: &[P],
+    options: &Options,
+) -> Result<Vec<Entry>, HuffmanError> {
+    let mut out = BufWriter::new(output);
+    out.write_all(&MAGIC)?;
+    out.write_all(&[VERSION])?;
+    let encoder = Encoder::new(options.clone());
+    let mut entradas = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        match path.file_name().map(|n| n.to_str()) {
+            Some(Some(nome)) => {
+                adicionar(&mut out, path, &mut vec![nome.to_owned()], &encoder, &mut entradas)?
+            }
+            None if path.is_dir() => {
+                adicionar_filhos(&mut out, path, &mut Vec::new(), &encoder, &mut entradas)?
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("caminho sem nome valido: {}", path.display()),
+                )
+                .into())
+            }
+        }
+    }
+    write_varint_em(&mut out, 0)?;
+    out.flush()?;
+    Ok(entradas)
+}
+
+fn write_varint_em<W: Write>(out: &mut W, v: u64) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(10);
+    write_varint(v, &mut buf);
+    out.write_all(&buf)
+}
+
+//percorre as entradas; `f` recebe cada uma com o reader posicionado no
+//inicio dos dados e devolve se os consumiu. os dados não consumidos são
+//pulados com seek
+fn percorrer<R: Read + Seek>(
+    input: R,
+    mut f: impl FnMut(&Entry, &mut BufReader<R>) -> Result<bool, HuffmanError>,
+) -> Result<(), HuffmanError> {
+    let mut input = BufReader::new(input);
+    ler_magic(&mut input)?;
+    while let Some(entry) = ler_entrada(&mut input)? {
+        if entry.kind == EntryKind::Dir && entry.compressed_size != 0 {
+            return Err(HuffmanError::Corrupt("diretorio com dados"));
+        }
+        if !f(&entry, &mut input)? {
+            let pulo = i64::try_from(entry.compressed_size)
+                .map_err(|_| HuffmanError::Corrupt("tamanho compactado invalido"))?;
+            input.seek_relative(pulo)?;
+        }
+    }
+    Ok(())
+}
+
+//lista as entradas sem decodificar nada
+pub fn list<R: Read + Seek>(input: R) -> Result<Vec<Entry>, HuffmanError> {
+    let mut entradas = Vec::new();
+    percorrer(input, |entry, _| {
+        entradas.push(entry.clone());
+        Ok(false)
+    })?;
+    Ok(entradas)
+}
+
+//descompacta os dados de uma entrada, conferindo o tamanho registrado
+fn descompactar<R: Read, W: Write>(entry: &Entry, input: &mut R, out: W) -> Result<(), HuffmanError> {
+    let mut dados = input.take(entry.compressed_size);
+    let found = Decoder::new().decompress(&mut dados, out)?;
+    if dados.limit() != 0 {
+        return Err(HuffmanError::Corrupt("dados sobrando depois do membro"));
+    }
+    if found != entry.size {
+        return Err(HuffmanError::LengthMismatch { expected: entry.size, found });
+    }
+    Ok(())
+}
+
+//recusa caminhos absolutos ou com "..", que escreveriam fora do destino
+fn caminho_seguro(dest: &Path, path: &str) -> Result<PathBuf, HuffmanError> {
+    let relativo = Path::new(path);
+    let mut out = dest.to_path_buf();
+    for componente in relativo.components() {
+        match componente {
+            Component::Normal(c) => out.push(c),
+            Component::CurDir => {}
+            _ => return Err(HuffmanError::Corrupt("caminho fora do destino")),
+        }
+    }
+    if out == dest {
+        return Err(HuffmanError::Corrupt("caminho vazio"));
+    }
+    Ok(out)
+}
+
+//só rwx para dono, grupo e outros
+const PERMISSOES_COMUNS: u32 = 0o777;
+
+fn aplicar_metadados(path: &Path, entry: &Entry, bits_especiais: bool) -> io::Result<()> {
+    //no Windows set_modified precisa de acesso de escrita, e diretorios não
+    //abrem sem FILE_FLAG_BACKUP_SEMANTICS: lá eles ficam com o mtime da extração
+    match entry.kind {
+        EntryKind::File => {
+            File::options().write(true).open(path)?.set_modified(modified(entry))?
+        }
+        EntryKind::Dir if cfg!(unix) => File::open(path)?.set_modified(modified(entry))?,
+        EntryKind::Dir => {}
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mascara = if bits_especiais { 0o7777 } else { PERMISSOES_COMUNS };
+        fs::set_permissions(path, fs::Permissions::from_mode(entry.mode & mascara))?;
+    }
+    #[cfg(not(unix))]
+    let _ = bits_especiais;
+    Ok(())
+}
+
+//extrai tudo dentro de `dest`, sem setuid, setgid e sticky
+pub fn extract_all<R: Read + Seek>(input: R, dest: &Path) -> Result<Vec<Entry>, HuffmanError> {
+    extract_all_with(input, dest, false)
+}
+
+//como extract_all; com `bits_especiais` aplica também setuid, setgid e
+//sticky. permissões e mtime dos diretorios são aplicados no fim, para um
+//diretorio somente leitura não impedir a extração do que está dentro dele
+pub fn extract_all_with<R: Read + Seek>(
+    input: R,
+    dest: &Path,
+    bits_especiais: bool,
+) -> Result<Vec<Entry>, HuffmanError> {
+    let mut entradas = Vec::new();
+    let mut diretorios = Vec::new();
+    percorrer(input, |entry, input| {
+        let path = caminho_seguro(dest, &entry.path)?;
+        entradas.push(entry.clone());
+        match entry.kind {
+            EntryKind::Dir => {
+                fs::create_dir_all(&path)?;
+                diretorios.push((path, entry.clone()));
+                Ok(false)
+            }
+            EntryKind::File => {
+                if let Some(pai) = path.parent() {
+                    fs::create_dir_all(pai)?;
+                }
+                descompactar(entry, input, File::create(&path)?)?;
+                aplicar_metadados(&path, entry, bits_especiais)?;
+                Ok(true)
+            }
+        }
+    })?;
+    for (path, entry) in diretorios.iter().rev() {
+        aplicar_metadados(path, entry, bits_especiais)?;
+    }
+    Ok(entradas)
+}
+
+//escreve um único membro em `output`, pulando os dados dos outros
+pub fn extract<R: Read + Seek, W: Write>(
+    input: R,
+    path: &str,
+    output: W,
+) -> Result<Entry, HuffmanError> {
+    let mut output = Some(output);
+    let mut achado = None;
+    percorrer(input, |entry, input| {
+        if achado.is_some() || entry.path != path || entry.kind != EntryKind::File {
+            return Ok(false);
+        }
+        if let Some(output) = output.take() {
+            descompactar(entry, input, output)?;
+        }
+        achado = Some(entry.clone());
+        Ok(true)
+    })?;
+    achado.ok_or_else(|| HuffmanError::NotFound(path.to_owned()))
+}
+
+//instante de modificação gravado numa entrada
+pub fn modified(entry: &Entry) -> SystemTime {
+    if entry.mtime >= 0 {
+        UNIX_EPOCH + Duration::from_secs(entry.mtime as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(entry.mtime.unsigned_abs())
+    }
+}
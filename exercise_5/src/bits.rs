@@ -0,0 +1,284 @@
+//leitura e escrita de bits com acumulador u64, bit mais significativo
+//primeiro (a mesma ordem que o antigo bits_to_bytes usava)
+use std::io::{self, Read, Write};
+
+pub struct BitWriter<W: Write> {
+    inner: W,
+    acc: u64,
+    //bits pendentes no acumulador (sempre < 32 entre chamadas)
+    n: u32,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(inner: W) -> Self {
+        BitWriter { inner, acc: 0, n: 0 }
+    }
+
+    //escreve os `len` bits menos significativos de `code`
+    pub fn write_bits(&mut self, code: u64, len: u32) -> io::Result<()> {
+        if len > 32 {
+            self.write_bits(code >> 32, len - 32)?;
+            return self.write_bits(code & 0xFFFF_FFFF, 32);
+        }
+        if len == 0 {
+            return Ok(());
+        }
+        self.acc = (self.acc << len) | (code & ((1u64 << len) - 1));
+        self.n += len;
+        if self.n >= 32 {
+            self.n -= 32;
+            let palavra = (self.acc >> self.n) as u32;
+            self.acc &= (1u64 << self.n) - 1;
+            self.inner.write_all(&palavra.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    //descarrega o acumulador, completando o ultimo byte com zeros
+    pub fn finish(mut self) -> io::Result<W> {
+        let bytes = self.n.div_ceil(8);
+        let alinhado = self.acc << (bytes * 8 - self.n);
+        for i in (0..bytes).rev() {
+            self.inner.write_all(&[(alinhado >> (i * 8)) as u8])?;
+        }
+        Ok(self.inner)
+    }
+}
+
+//tamanho do buffer interno do BitReader
+const BUFFER: usize = 8 * 1024;
+
+pub struct BitReader<R: Read> {
+    inner: R,
+    acc: u64,
+    //bits ainda não consumidos no acumulador
+    n: u32,
+    total: u64,
+    //bytes já lidos de `inner` que ainda não entraram no acumulador:
+    //buf[pos..fim]
+    buf: Box<[u8]>,
+    pos: usize,
+    fim: usize,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(inner: R) -> Self {
+        BitReader { inner, acc: 0, n: 0, total: 0, buf: vec![0; BUFFER].into(), pos: 0, fim: 0 }
+    }
+
+    //completa o acumulador. com 8 bytes no buffer entra uma palavra inteira
+    //de uma vez; perto do fim do buffer (ou dos dados) vai byte a byte
+    fn refill(&mut self) -> io::Result<()> {
+        if self.n <= 56 && self.fim - self.pos >= 8 {
+            let cabem = (64 - self.n) / 8;
+            let palavra = &self.buf[self.pos..self.pos + 8];
+            let v = u64::from_be_bytes(palavra.try_into().unwrap()) >> (64 - 8 * cabem);
+            self.acc = self.acc.checked_shl(8 * cabem).unwrap_or(0) | v;
+            self.n += 8 * cabem;
+            self.pos += cabem as usize;
+            return Ok(());
+        }
+        while self.n <= 56 {
+            if self.pos == self.fim && !self.encher()? {
+                break;
+            }
+            self.acc = (self.acc << 8) | self.buf[self.pos] as u64;
+            self.n += 8;
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    //traz mais bytes de `inner` para o buffer; false no fim dos dados
+    fn encher(&mut self) -> io::Result<bool> {
+        self.buf.copy_within(self.pos..self.fim, 0);
+        self.fim -= self.pos;
+        self.pos = 0;
+        loop {
+            match self.inner.read(&mut self.buf[self.fim..]) {
+                Ok(0) => return Ok(false),
+                Ok(lidos) => {
+                    self.fim += lidos;
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    //olha os proximos `n` bits (n <= 56) sem consumir. devolve o valor e
+    //quantos deles existem de fato; no fim dos dados os que faltam vêm zerados
+    pub fn peek(&mut self, n: u32) -> io::Result<(u64, u32)> {
+        if self.n < n {
+            self.refill()?;
+        }
+        let mascara = (1u64 << n) - 1;
+        if self.n >= n {
+            Ok(((self.acc >> (self.n - n)) & mascara, n))
+        } else {
+            Ok(((self.acc << (n - self.n)) & mascara, self.n))
+        }
+    }
+
+    //descarta `n` bits já vistos com peek
+    pub fn consume(&mut self, n: u32) {
+        self.n -= n;
+        self.total += n as u64;
+    }
+
+    //lê um bit; None quando os dados acabaram
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        if self.n == 0 {
+            self.refill()?;
+            if self.n == 0 {
+                return Ok(None);
+            }
+        }
+        self.n -= 1;
+        self.total += 1;
+        Ok(Some((self.acc >> self.n) & 1 == 1))
+    }
+
+    //quantidade de bits consumidos até agora
+    pub fn bits_read(&self) -> u64 {
+        self.total
+    }
+
+    //o reader de dentro. os bytes que já estavam no buffer ficam para trás,
+    //então só serve para descartar o resto de um reader limitado (take)
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+//ordem do DEFLATE: bit menos significativo primeiro. os códigos de Huffman
+//precisam chegar aqui já invertidos
+pub struct LsbWriter<W: Write> {
+    inner: W,
+    acc: u64,
+    n: u32,
+}
+
+impl<W: Write> LsbWriter<W> {
+    pub fn new(inner: W) -> Self {
+        LsbWriter { inner, acc: 0, n: 0 }
+    }
+
+    //escreve os `len` (<= 32) bits menos significativos de `v`
+    pub fn write_bits(&mut self, v: u64, len: u32) -> io::Result<()> {
+        self.acc |= (v & ((1u64 << len) - 1)) << self.n;
+        self.n += len;
+        if self.n >= 32 {
+            self.inner.write_all(&(self.acc as u32).to_le_bytes())?;
+            self.acc >>= 32;
+            self.n -= 32;
+        }
+        Ok(())
+    }
+
+    //completa o byte atual com zeros
+    pub fn align(&mut self) -> io::Result<()> {
+        self.write_bits(0, (8 - self.n % 8) % 8)?;
+        while self.n > 0 {
+            self.inner.write_all(&[self.acc as u8])?;
+            self.acc >>= 8;
+            self.n -= 8;
+        }
+        Ok(())
+    }
+
+    //bytes crus, depois de align
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.inner.write_all(bytes)
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.align()?;
+        Ok(self.inner)
+    }
+}
+
+pub struct LsbReader<R: Read> {
+    inner: R,
+    acc: u64,
+    n: u32,
+}
+
+impl<R: Read> LsbReader<R> {
+    pub fn new(inner: R) -> Self {
+        LsbReader { inner, acc: 0, n: 0 }
+    }
+
+    //como em BitReader: os bytes que cabem no acumulador num read só
+    fn refill(&mut self) -> io::Result<()> {
+        let mut bytes = [0u8; 8];
+        while self.n <= 56 {
+            let cabem = ((64 - self.n) / 8) as usize;
+            match self.inner.read(&mut bytes[..cabem]) {
+                Ok(0) => break,
+                Ok(lidos) => {
+                    let v = u64::from_le_bytes(bytes) & (u64::MAX >> (64 - 8 * lidos));
+                    self.acc |= v << self.n;
+                    self.n += 8 * lidos as u32;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    //olha os proximos `n` bits (n <= 56); devolve o valor e quantos existem
+    pub fn peek(&mut self, n: u32) -> io::Result<(u64, u32)> {
+        if self.n < n {
+            self.refill()?;
+        }
+        Ok((self.acc & ((1u64 << n) - 1), self.n.min(n)))
+    }
+
+    pub fn consume(&mut self, n: u32) {
+        self.acc >>= n;
+        self.n -= n;
+    }
+
+    //lê `n` bits; erro de fim de arquivo se não houver tantos
+    pub fn read_bits(&mut self, n: u32) -> io::Result<u64> {
+        let (v, disponiveis) = self.peek(n)?;
+        if disponiveis < n {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.consume(n);
+        Ok(v)
+    }
+
+    //descarta o resto do byte atual
+    pub fn align(&mut self) {
+        self.consume(self.n % 8);
+    }
+
+    //bytes inteiros depois de align: primeiro o que sobrou no acumulador
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let mut i = 0;
+        while i < buf.len() && self.n >= 8 {
+            buf[i] = self.acc as u8;
+            self.consume(8);
+            i += 1;
+        }
+        self.inner.read_exact(&mut buf[i..])
+    }
+
+    //como read_bytes, mas devolve quantos bytes havia (0 no fim dos dados)
+    pub fn read_some(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut i = 0;
+        while i < buf.len() && self.n >= 8 {
+            buf[i] = self.acc as u8;
+            self.consume(8);
+            i += 1;
+        }
+        if i < buf.len() {
+            i += self.inner.by_ref().take((buf.len() - i) as u64).read(&mut buf[i..])?;
+        }
+        Ok(i)
+    }
+}
@@ -0,0 +1,294 @@
+//pipeline no estilo do bzip2 na frente do Huffman: transformada de
+//Burrows-Wheeler, move-to-front e as sequencias de zeros do MTF codificadas
+//em base 2 bijetiva. a BWT agrupa bytes que aparecem antes de contextos
+//parecidos, o MTF transforma isso em números pequenos (muitos zeros) e o
+//Huffman estático fecha a conta. cada bloco é transformado sozinho.
+//
+//a ordem das linhas vem do arranjo de sufixos, construido com SA-IS em
+//tempo linear; um terminador virtual no fim do bloco faz a ordem dos
+//sufixos coincidir com a das rotações.
+//
+//simbolos gravados:
+//  0 (RUNA) e 1 (RUNB)  digitos de uma sequencia de k zeros, k em base 2
+//                       bijetiva com o digito menos significativo primeiro
+//                       (RUNA vale 1 e RUNB vale 2 na posição)
+//  2..=256              valor v do MTF (1..=255) como v + 1
+use std::io::{Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::HuffmanError;
+use crate::huffman::DecodeTable;
+
+pub const MAX_SYMBOL: u32 = 256;
+//maior bloco aceito (a transformada precisa do bloco inteiro em memoria)
+pub const MAX_BLOCK: usize = 16 << 20;
+const RUNA: u32 = 0;
+const RUNB: u32 = 1;
+const VAZIO: u32 = u32::MAX;
+
+//arranjo de sufixos por SA-IS (Nong, Zhang e Chan), em tempo linear. os
+//simbolos de `s` vão de 0 a `upper`; a recursão trabalha sobre os nomes das
+//substrings LMS
+fn sa_is<T: Copy + Into<u32>>(s: &[T], upper: usize) -> Vec<u32> {
+    let n = s.len();
+    let v = |i: usize| s[i].into() as usize;
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if v(0) < v(1) { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    //tipo S (true) ou L (false) de cada sufixo
+    let mut tipo_s = vec![false; n];
+    for i in (0..n - 1).rev() {
+        tipo_s[i] = if v(i) == v(i + 1) { tipo_s[i + 1] } else { v(i) < v(i + 1) };
+    }
+    //inicio dos baldes de L e de S de cada simbolo
+    let mut soma_l = vec![0u32; upper + 1];
+    let mut soma_s = vec![0u32; upper + 1];
+    for i in 0..n {
+        if !tipo_s[i] {
+            soma_s[v(i)] += 1;
+        } else if v(i) < upper {
+            soma_l[v(i) + 1] += 1;
+        }
+    }
+    for i in 0..=upper {
+        soma_s[i] += soma_l[i];
+        if i < upper {
+            soma_l[i + 1] += soma_s[i];
+        }
+    }
+    let mut sa = vec![VAZIO; n];
+    let induzir = |sa: &mut Vec<u32>, lms: &[u32]| {
+        sa.fill(VAZIO);
+        let mut balde = soma_s.clone();
+        for &d in lms {
+            let d = d as usize;
+            sa[balde[v(d)] as usize] = d as u32;
+            balde[v(d)] += 1;
+        }
+        balde.copy_from_slice(&soma_l);
+        sa[balde[v(n - 1)] as usize] = (n - 1) as u32;
+        balde[v(n - 1)] += 1;
+        for i in 0..n {
+            let x = sa[i];
+            if x != VAZIO && x >= 1 && !tipo_s[x as usize - 1] {
+                let c = v(x as usize - 1);
+                sa[balde[c] as usize] = x - 1;
+                balde[c] += 1;
+            }
+        }
+        balde.copy_from_slice(&soma_l);
+        for i in (0..n).rev() {
+            let x = sa[i];
+            if x != VAZIO && x >= 1 && tipo_s[x as usize - 1] {
+                let c = v(x as usize - 1) + 1;
+                balde[c] -= 1;
+                sa[balde[c] as usize] = x - 1;
+            }
+        }
+    };
+
+    let mut lms_map = vec![VAZIO; n + 1];
+    let mut lms = Vec::new();
+    for i in 1..n {
+        if !tipo_s[i - 1] && tipo_s[i] {
+            lms_map[i] = lms.len() as u32;
+            lms.push(i as u32);
+        }
+    }
+    let m = lms.len();
+    induzir(&mut sa, &lms);
+    if m > 0 {
+        let mut ordenados: Vec<u32> =
+            sa.iter().copied().filter(|&x| lms_map[x as usize] != VAZIO).collect();
+        //nomeia as substrings LMS; iguais recebem o mesmo nome
+        let mut nomes = vec![0u32; m];
+        let mut nome = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (ordenados[i - 1] as usize, ordenados[i] as usize);
+            let fim = |x: usize| {
+                let k = lms_map[x] as usize + 1;
+                if k < m { lms[k] as usize } else { n }
+            };
+            let (fim_l, fim_r) = (fim(l), fim(r));
+            let mut iguais = fim_l - l == fim_r - r;
+            if iguais {
+                while l < fim_l && v(l) == v(r) {
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || v(l) != v(r) {
+                    iguais = false;
+                }
+            }
+            if !iguais {
+                nome += 1;
+            }
+            nomes[lms_map[ordenados[i] as usize] as usize] = nome;
+        }
+        let sa_nomes = sa_is(&nomes, nome as usize);
+        for (o, &i) in ordenados.iter_mut().zip(&sa_nomes) {
+            *o = lms[i as usize];
+        }
+        induzir(&mut sa, &ordenados);
+    }
+    sa
+}
+
+//BWT com um terminador virtual menor que todos os bytes: as linhas são os
+//n + 1 sufixos de data + terminador em ordem. devolve a ultima coluna sem o
+//terminador e a linha onde ele estava (de 1 a n; a linha 0 é o sufixo
+//vazio)
+pub fn transform(data: &[u8]) -> (Vec<u8>, usize) {
+    let n = data.len();
+    let mut ultima = Vec::with_capacity(n);
+    if n == 0 {
+        return (ultima, 0);
+    }
+    ultima.push(data[n - 1]);
+    let mut origem = 0;
+    for (i, &pos) in sa_is(data, 255).iter().enumerate() {
+        if pos == 0 {
+            origem = i + 1;
+        } else {
+            ultima.push(data[pos as usize - 1]);
+        }
+    }
+    (ultima, origem)
+}
+
+//desfaz a transformada a partir da ultima coluna e da linha do terminador
+pub fn inverse(ultima: &[u8], origem: usize) -> Result<Vec<u8>, HuffmanError> {
+    let n = ultima.len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    if origem == 0 || origem > n {
+        return Err(HuffmanError::Corrupt("origem da BWT fora do bloco"));
+    }
+    //inicio de cada byte na primeira coluna (a linha 0 é do terminador)
+    let mut inicio = [0u32; 256];
+    for &b in ultima {
+        inicio[b as usize] += 1;
+    }
+    let mut acc = 1;
+    for v in inicio.iter_mut() {
+        let qtd = *v;
+        *v = acc;
+        acc += qtd;
+    }
+    //lf[linha]: linha do sufixo que começa um byte antes. a linha do
+    //terminador não tem sucessor e fica com 0
+    let mut lf = Vec::with_capacity(n + 1);
+    for linha in 0..=n {
+        if linha == origem {
+            lf.push(0);
+            continue;
+        }
+        let b = ultima[if linha < origem { linha } else { linha - 1 }] as usize;
+        lf.push(inicio[b]);
+        inicio[b] += 1;
+    }
+    let mut out = vec![0u8; n];
+    let mut linha = 0;
+    for i in (0..n).rev() {
+        out[i] = ultima[if linha < origem { linha } else { linha - 1 }];
+        linha = lf[linha] as usize;
+    }
+    Ok(out)
+}
+
+//move-to-front seguido da codificação das sequencias de zeros
+pub fn mtf_rle(ultima: &[u8]) -> Vec<u32> {
+    let mut lista: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut out = Vec::with_capacity(ultima.len() / 2);
+    let mut zeros = 0usize;
+    for &b in ultima {
+        let pos = lista.iter().position(|&x| x == b).unwrap();
+        if pos == 0 {
+            zeros += 1;
+            continue;
+        }
+        sequencia(zeros, &mut out);
+        zeros = 0;
+        lista.copy_within(0..pos, 1);
+        lista[0] = b;
+        out.push(pos as u32 + 1);
+    }
+    sequencia(zeros, &mut out);
+    out
+}
+
+//k zeros em base 2 bijetiva: RUNA vale 1 e RUNB vale 2 em cada posição
+fn sequencia(mut k: usize, out: &mut Vec<u32>) {
+    while k > 0 {
+        if k & 1 == 1 {
+            out.push(RUNA);
+            k -= 1;
+        } else {
+            out.push(RUNB);
+            k -= 2;
+        }
+        k >>= 1;
+    }
+}
+
+pub fn write_symbols<W: Write>(
+    simbolos: &[u32],
+    codes: &[(u64, u8)],
+    bits: &mut BitWriter<W>,
+) -> std::io::Result<()> {
+    for &s in simbolos {
+        let (code, len) = codes[s as usize];
+        bits.write_bits(code, len as u32)?;
+    }
+    Ok(())
+}
+
+//lê simbolos até completar `raw_len` bytes da ultima coluna, desfaz o MTF
+//e a transformada e escreve o bloco original
+pub fn decode<R: Read, W: Write>(
+    raw_len: u64,
+    origem: u64,
+    table: &DecodeTable,
+    bits: &mut BitReader<R>,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    if raw_len > MAX_BLOCK as u64 || origem == 0 || origem > raw_len {
+        return Err(HuffmanError::Corrupt("bloco BWT invalido"));
+    }
+    let raw_len = raw_len as usize;
+    let mut lista: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut ultima = Vec::with_capacity(raw_len);
+    //sequencia de zeros em andamento e o peso do proximo digito
+    let mut zeros = 0usize;
+    let mut peso = 1usize;
+    while ultima.len() + zeros < raw_len {
+        let sym = table.decode(bits)?.ok_or(HuffmanError::Corrupt("payload menor que o bloco"))?;
+        match sym {
+            RUNA | RUNB => {
+                zeros += peso << sym;
+                peso <<= 1;
+                if ultima.len() + zeros > raw_len {
+                    return Err(HuffmanError::Corrupt("sequencia de zeros passa do bloco"));
+                }
+            }
+            2..=MAX_SYMBOL => {
+                ultima.resize(ultima.len() + zeros, lista[0]);
+                zeros = 0;
+                peso = 1;
+                let pos = sym as usize - 1;
+                let b = lista[pos];
+                lista.copy_within(0..pos, 1);
+                lista[0] = b;
+                ultima.push(b);
+            }
+            _ => return Err(HuffmanError::Corrupt("simbolo BWT invalido")),
+        }
+    }
+    ultima.resize(raw_len, lista[0]);
+    out.write_all(&inverse(&ultima, origem as usize)?)?;
+    Ok(())
+}
@@ -0,0 +1,1087 @@
+//formato .huff versionado
+//
+//cabeçalho (15 bytes):
+//  0..4   magic b"HUF\x1a"
+//  4      versão do formato (2)
+//  5      flags: bit 0 = alfabeto de chars (senão bytes); bit 1 = há um
+//         indice de busca depois do trailer; bit 2 = blocos do método
+//         estático codificados com rANS em vez de Huffman; bit 3 = cada
+//         bloco termina com o CRC dos seus bytes originais; bit 4 = os
+//         blocos do método estático dizem se passaram pelo RLE; os outros
+//         bits são reservados e precisam ser 0
+//  6      método (Method): 0 = Huffman estático, 1 = Huffman adaptativo,
+//         2 = LZ77 + Huffman, 3 = BWT + MTF + Huffman, 4 = Huffman com
+//         dicionario treinado, 5 = tokens (palavras) + Huffman, 6 = Huffman
+//         de ordem 1 ou 2 (uma tabela por contexto)
+//  7..15  tamanho original em bytes (u64 big-endian); u64::MAX quando o
+//         compactador lê de um pipe e não sabe o tamanho de antemão
+//a versão 1 não tinha o byte de método (sempre estático) e continua sendo
+//lida. no método 4 o cabeçalho tem mais 4 bytes:
+//  15..19 id do dicionario (dictionary.rs), u32 big-endian
+//corpo: lista de blocos terminada por um varint 0. no método estático cada
+//bloco é
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//          tabela canônica de tamanhos de código (huffman::write_code_lengths)
+//  varint  tamanho do payload em bytes
+//          payload (códigos canônicos, bit mais significativo primeiro)
+//com o bit 4 das flags (só no Huffman estático) o bloco tem mais um byte
+//logo depois dos bytes originais: 1 quando os simbolos passaram pelo RLE
+//(rle.rs), com rle::RUN na tabela e as repetições no payload, 0 quando não.
+//o compactador mede os dois jeitos em cada bloco e grava o menor
+//no BWT (bwt.rs) o alfabeto também é sempre o de bytes e o bloco guarda
+//a linha da rotação original:
+//  varint  bytes originais cobertos pelo bloco (> 0, no maximo 16 MiB)
+//  varint  linha do terminador na BWT (1..=bytes originais)
+//          tabela de tamanhos de código (simbolos 0..=256)
+//  varint  tamanho do payload em bytes
+//          payload (sequencias de zeros e valores do MTF)
+//com o bit do rANS (rans.rs) o bloco do método estático troca a tabela de
+//tamanhos de código pela de frequencias normalizadas:
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//          tabela de frequencias (rans::write_frequencies)
+//  varint  tamanho do payload em bytes
+//          payload (estado e palavras do rANS)
+//no método de tokens (tokens.rs) o alfabeto é o de bytes e o bloco traz o
+//seu vocabulario antes da tabela:
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//          vocabulario (tokens::write_vocabulary), simbolos 256..
+//          tabela de tamanhos de código (bytes literais e tokens)
+//  varint  tamanho do payload em bytes
+//          payload
+//no método de contexto (context.rs) o alfabeto é o de bytes e a tabela
+//única dá lugar às tabelas dos contextos:
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//          ordem e tabelas (context::write_tables)
+//  varint  tamanho do payload em bytes
+//          payload (cada byte com a tabela do seu contexto)
+//com dicionario a tabela é a dele, e o bloco não guarda nenhuma:
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//  varint  tamanho do payload em bytes
+//          payload (códigos do dicionario, com escapes)
+//no adaptativo não há tabela: o modelo continua de um bloco para o outro e
+//cada bloco é só [varint bytes originais][varint tamanho][payload]. no
+//LZ77 o alfabeto é sempre o de bytes e o bloco tem duas tabelas, a de
+//literais/comprimentos e a de distancias (vazia se não houver referencias):
+//  varint  bytes originais cobertos pelo bloco (> 0)
+//          tabela de literais/comprimentos (simbolos 0..=285)
+//          tabela de distancias (simbolos 0..=47)
+//  varint  tamanho do payload em bytes
+//          payload (tokens do lz77.rs)
+//com o bit 3 das flags (todos os métodos menos o adaptativo) cada bloco
+//acaba com mais
+//  u32     CRC-32 dos bytes originais do bloco, big-endian
+//o que deixa o descompactador apontar o bloco estragado em vez de só
+//descobrir no fim que o arquivo não confere
+//trailer:
+//  u32 big-endian com o CRC-32 dos dados originais
+//indice de busca (só com o bit 1 das flags, nos métodos estático e LZ77):
+//  varint  quantidade de pontos
+//          cada ponto: varint com o deslocamento, desde o ponto anterior, do
+//          inicio do bloco no arquivo compactado, e varint com o mesmo no
+//          arquivo original. o primeiro ponto é relativo a (0, 0) e o ultimo
+//          aponta para o marcador de fim, com o tamanho original total
+//  u64     posição do inicio do indice no arquivo, big-endian
+//  4       magic b"HUFI"
+//os blocos começam sempre num byte inteiro (a posição em bits é 8 vezes a
+//gravada) e não dependem dos anteriores, então cada ponto é um lugar onde
+//o decodificador pode recomeçar do zero. há um ponto por bloco, o que
+//deixa o intervalo entre eles igual ao tamanho do bloco. quem lê o arquivo
+//do começo ao fim só confere o indice contra o total decodificado;
+//stream::decompress_file usa os pontos para decodificar os blocos em
+//paralelo, cada um escrito na sua posição da saida
+//
+//o modo de duas passadas grava um único bloco com o arquivo inteiro; o modo
+//de blocos grava um bloco a cada N bytes (1 MiB por padrão), cada um com a
+//sua tabela, o que permite codificar e decodificar blocos em paralelo e
+//acompanha estatisticas que mudam ao longo do arquivo. o adaptativo grava
+//um bloco a cada leitura da entrada, para a saida sair logo.
+//
+//casos degenerados:
+//  - arquivo vazio: nenhum bloco, só o cabeçalho, o varint 0 e o CRC-32 de
+//    nada (0), 20 bytes no total (o bit 3 das flags não muda o tamanho, já
+//    que não há blocos)
+//  - bloco com um único simbolo distinto ("aaaa"): a tabela tem um simbolo
+//    com código de tamanho 0 e o payload tem 0 bytes; o descompactador
+//    repete o simbolo até completar os bytes originais do bloco. no LZ77 o
+//    mesmo vale para cada uma das duas tabelas
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use crate::adaptive::AdaptiveModel;
+use crate::bits::{BitReader, BitWriter};
+use crate::bwt;
+use crate::context;
+use crate::crc32::{Crc32, CrcWriter};
+use crate::deflate::{self, Wrapper};
+use crate::dictionary::Dictionary;
+use crate::error::HuffmanError;
+use crate::huffman::{
+    canonical_codes, contar_frequencias, lengths_are_valid, limited_code_lengths,
+    read_code_lengths, read_varint, write_code_lengths, write_varint, Alphabet, DecodeTable,
+};
+use crate::legacy;
+use crate::lz77;
+use crate::rans::{self, RansDecoder};
+use crate::rle;
+use crate::stream;
+use crate::tokens;
+
+pub const MAGIC: [u8; 4] = *b"HUF\x1a";
+pub const VERSION: u8 = 2;
+pub const HEADER_LEN: usize = 15;
+pub const UNKNOWN_LEN: u64 = u64::MAX;
+const FLAG_CHARS: u8 = 0b0000_0001;
+const FLAG_INDEX: u8 = 0b0000_0010;
+const FLAG_RANS: u8 = 0b0000_0100;
+const FLAG_BLOCK_CRC: u8 = 0b0000_1000;
+const FLAG_RLE: u8 = 0b0001_0000;
+pub const INDEX_MAGIC: [u8; 4] = *b"HUFI";
+//posição do indice e INDEX_MAGIC, no fim do arquivo
+const INDEX_FOOTER_LEN: usize = 12;
+
+//formatos que o leitor reconhece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Huff,
+    //arvore serializada gravada pelo antigo main.rs (compactado.bin)
+    LegacyTree,
+    //tabela de frequencias gravada pelo antigo compactador.rs (compactado.huff)
+    LegacyFreqTable,
+    //DEFLATE nos envelopes do gzip e do zlib (deflate.rs)
+    Gzip,
+    Zlib,
+}
+
+pub fn detect(data: &[u8]) -> Option<Format> {
+    if data.starts_with(&MAGIC) {
+        Some(Format::Huff)
+    } else if let Some(wrapper) = deflate::detect(data) {
+        Some(match wrapper {
+            Wrapper::Gzip => Format::Gzip,
+            Wrapper::Zlib => Format::Zlib,
+        })
+    } else if legacy::parece_arvore(data) {
+        Some(Format::LegacyTree)
+    } else if legacy::parece_tabela(data) {
+        Some(Format::LegacyFreqTable)
+    } else {
+        None
+    }
+}
+
+//como os blocos do corpo são codificados
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    //tabela canônica por bloco
+    Static,
+    //FGK, sem tabela (adaptive.rs)
+    Adaptive,
+    //LZ77 com tabelas de literais/comprimentos e distancias por bloco
+    Lz77,
+    //BWT, move-to-front e sequencias de zeros antes do Huffman (bwt.rs)
+    Bwt,
+    //tabela de um dicionario treinado, referenciado pelo id (dictionary.rs)
+    Dictionary,
+    //palavras e separadores (ou tokens dados) como simbolos (tokens.rs)
+    Tokens,
+    //uma tabela por contexto de 1 ou 2 bytes anteriores (context.rs)
+    Context,
+}
+
+impl Method {
+    fn from_byte(b: u8) -> Option<Method> {
+        match b {
+            0 => Some(Method::Static),
+            1 => Some(Method::Adaptive),
+            2 => Some(Method::Lz77),
+            3 => Some(Method::Bwt),
+            4 => Some(Method::Dictionary),
+            5 => Some(Method::Tokens),
+            6 => Some(Method::Context),
+            _ => None,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Method::Static => 0,
+            Method::Adaptive => 1,
+            Method::Lz77 => 2,
+            Method::Bwt => 3,
+            Method::Dictionary => 4,
+            Method::Tokens => 5,
+            Method::Context => 6,
+        }
+    }
+}
+
+//codificador de entropia dos blocos do método estático
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coder {
+    //códigos canônicos de tamanho inteiro
+    Huffman,
+    //rANS com frequencias normalizadas (rans.rs)
+    Rans,
+}
+
+//campos do cabeçalho depois do magic
+#[derive(Debug, Clone, Copy)]
+pub struct Header {
+    pub alphabet: Alphabet,
+    pub method: Method,
+    //None quando o arquivo foi gravado a partir de um pipe
+    pub original_len: Option<u64>,
+    //o arquivo termina com um indice de busca (SeekIndex)
+    pub indexed: bool,
+    pub coder: Coder,
+    //id do dicionario, só no método Dictionary
+    pub dictionary: Option<u32>,
+    //cada bloco termina com o CRC dos seus bytes originais
+    pub block_crc: bool,
+    //cada bloco diz se passou pelo RLE (só no Huffman estático)
+    pub rle: bool,
+}
+
+impl Header {
+    //bytes do cabeçalho gravado, contando o magic
+    pub fn encoded_len(&self) -> usize {
+        HEADER_LEN + if self.dictionary.is_some() { 4 } else { 0 }
+    }
+}
+
+pub fn write_header<W: Write>(out: &mut W, header: &Header) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(HEADER_LEN);
+    buf.extend_from_slice(&MAGIC);
+    buf.push(VERSION);
+    let mut flags = if header.alphabet == Alphabet::Chars { FLAG_CHARS } else { 0 };
+    if header.indexed {
+        flags |= FLAG_INDEX;
+    }
+    if header.coder == Coder::Rans {
+        flags |= FLAG_RANS;
+    }
+    if header.block_crc {
+        flags |= FLAG_BLOCK_CRC;
+    }
+    if header.rle {
+        flags |= FLAG_RLE;
+    }
+    buf.push(flags);
+    buf.push(header.method.to_byte());
+    buf.extend_from_slice(&header.original_len.unwrap_or(UNKNOWN_LEN).to_be_bytes());
+    if let Some(id) = header.dictionary {
+        buf.extend_from_slice(&id.to_be_bytes());
+    }
+    out.write_all(&buf)
+}
+
+//lê o resto do cabeçalho, depois que o magic já foi conferido
+pub fn read_header<R: Read>(input: &mut R) -> Result<Header, HuffmanError> {
+    ler_cabecalho(input).map_err(|e| match e {
+        HuffmanError::Truncated => HuffmanError::TruncatedHeader,
+        e => e,
+    })
+}
+
+fn ler_cabecalho<R: Read>(input: &mut R) -> Result<Header, HuffmanError> {
+    let mut buf = [0u8; 2];
+    input.read_exact(&mut buf)?;
+    let [version, flags] = buf;
+    if version == 0 || version > VERSION {
+        return Err(HuffmanError::UnsupportedVersion(version));
+    }
+    if flags & !(FLAG_CHARS | FLAG_INDEX | FLAG_RANS | FLAG_BLOCK_CRC | FLAG_RLE) != 0 {
+        return Err(HuffmanError::UnknownFlags(flags));
+    }
+    let alphabet = if flags & FLAG_CHARS != 0 { Alphabet::Chars } else { Alphabet::Bytes };
+    let method = if version >= 2 {
+        let mut b = [0u8; 1];
+        input.read_exact(&mut b)?;
+        Method::from_byte(b[0]).ok_or(HuffmanError::Corrupt("método desconhecido"))?
+    } else {
+        Method::Static
+    };
+    let indexed = flags & FLAG_INDEX != 0;
+    if indexed && method == Method::Adaptive {
+        return Err(HuffmanError::Corrupt("indice de busca no método adaptativo"));
+    }
+    let coder = if flags & FLAG_RANS != 0 { Coder::Rans } else { Coder::Huffman };
+    if coder == Coder::Rans && method != Method::Static {
+        return Err(HuffmanError::Corrupt("rANS só existe no método estático"));
+    }
+    let block_crc = flags & FLAG_BLOCK_CRC != 0;
+    if block_crc && method == Method::Adaptive {
+        return Err(HuffmanError::Corrupt("CRC por bloco no método adaptativo"));
+    }
+    let rle = flags & FLAG_RLE != 0;
+    if rle && (method != Method::Static || coder == Coder::Rans) {
+        return Err(HuffmanError::Corrupt("RLE só existe no Huffman estático"));
+    }
+    let mut len = [0u8; 8];
+    input.read_exact(&mut len)?;
+    let original_len = match u64::from_be_bytes(len) {
+        UNKNOWN_LEN => None,
+        n => Some(n),
+    };
+    let dictionary = if method == Method::Dictionary {
+        let mut id = [0u8; 4];
+        input.read_exact(&mut id)?;
+        Some(u32::from_be_bytes(id))
+    } else {
+        None
+    };
+    Ok(Header { alphabet, method, original_len, indexed, coder, dictionary, block_crc, rle })
+}
+
+//codifica um bloco inteiro (cabeçalho e payload) a partir dos bytes
+//originais. cada bloco é independente, então pode rodar em qualquer thread.
+//com `rle` (o bit 4 das flags) tenta também o RLE e fica com o menor
+pub fn encode_block(
+    data: &[u8],
+    alphabet: Alphabet,
+    max_code_len: u8,
+    rle: bool,
+) -> Result<Vec<u8>, HuffmanError> {
+    let symbols = alphabet.symbols(data)?;
+    let lengths = limited_code_lengths(&contar_frequencias(&symbols), max_code_len);
+    let codes = canonical_codes(&lengths);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 2));
+    for s in &symbols {
+        let (code, len) = codes[s];
+        bits.write_bits(code, len as u32)?;
+    }
+    let marcador = rle.then_some(0);
+    let out = montar_bloco(data.len() as u64, marcador, &lengths, &bits.finish()?);
+    if !rle {
+        return Ok(out);
+    }
+
+    let pecas = rle::runs(&symbols);
+    if pecas.len() == symbols.len() {
+        return Ok(out);
+    }
+    let lengths = limited_code_lengths(&rle::frequencies(&pecas), max_code_len);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 4));
+    rle::write_pieces(&pecas, &canonical_codes(&lengths), &mut bits)?;
+    let com_rle = montar_bloco(data.len() as u64, Some(1), &lengths, &bits.finish()?);
+    Ok(if com_rle.len() < out.len() { com_rle } else { out })
+}
+
+//bloco do método estático com o payload pronto. `rle` é o byte de RLE,
+//quando o cabeçalho pede
+fn montar_bloco(raw_len: u64, rle: Option<u8>, lengths: &[(u32, u8)], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 64);
+    write_varint(raw_len, &mut out);
+    out.extend(rle);
+    write_code_lengths(lengths, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(payload);
+    out
+}
+
+//bloco do método estático com rANS no lugar dos códigos de Huffman
+pub fn encode_rans_block(data: &[u8], alphabet: Alphabet) -> Result<Vec<u8>, HuffmanError> {
+    let symbols = alphabet.symbols(data)?;
+    let tabela = rans::normalize(&contar_frequencias(&symbols));
+    let payload = rans::encode(&symbols, &tabela);
+
+    let mut out = Vec::with_capacity(payload.len() + 64);
+    write_varint(data.len() as u64, &mut out);
+    rans::write_frequencies(&tabela, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//cabeçalho de um bloco cujo payload vai ser escrito depois, direto no
+//writer (modo de duas passadas). devolve os códigos a usar
+pub fn write_block_header<W: Write>(
+    out: &mut W,
+    freqs: &HashMap<u32, u64>,
+    raw_len: u64,
+    max_code_len: u8,
+) -> io::Result<HashMap<u32, (u64, u8)>> {
+    let lengths = limited_code_lengths(freqs, max_code_len);
+    let codes = canonical_codes(&lengths);
+    let bits: u64 = freqs.iter().map(|(s, &f)| f * codes[s].1 as u64).sum();
+
+    let mut cabecalho = Vec::new();
+    write_varint(raw_len, &mut cabecalho);
+    write_code_lengths(&lengths, &mut cabecalho);
+    write_varint(bits.div_ceil(8), &mut cabecalho);
+    out.write_all(&cabecalho)?;
+    Ok(codes)
+}
+
+//bloco do método LZ77: tokens do match finder codificados com uma tabela
+//para literais e comprimentos e outra para distancias
+pub fn encode_lz77_block(
+    data: &[u8],
+    window: usize,
+    level: u8,
+    max_code_len: u8,
+) -> Result<Vec<u8>, HuffmanError> {
+    let tokens = lz77::tokenize(data, window, level);
+    let mut litlen_freqs = HashMap::new();
+    let mut dist_freqs = HashMap::new();
+    for &token in &tokens {
+        let (litlen, dist) = lz77::symbols(token);
+        *litlen_freqs.entry(litlen).or_insert(0) += 1;
+        if let Some(dist) = dist {
+            *dist_freqs.entry(dist).or_insert(0) += 1;
+        }
+    }
+    let litlen_lengths = limited_code_lengths(&litlen_freqs, max_code_len);
+    let dist_lengths = limited_code_lengths(&dist_freqs, max_code_len);
+    let litlen_codes = tabela_de_codigos(&litlen_lengths);
+    let dist_codes = tabela_de_codigos(&dist_lengths);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 3));
+    lz77::write_tokens(&tokens, &litlen_codes, &dist_codes, &mut bits)?;
+    let payload = bits.finish()?;
+
+    let mut out = Vec::with_capacity(payload.len() + 256);
+    write_varint(data.len() as u64, &mut out);
+    write_code_lengths(&litlen_lengths, &mut out);
+    write_code_lengths(&dist_lengths, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//bloco do método BWT: transformada, MTF e sequencias de zeros, com uma
+//tabela de Huffman para os simbolos resultantes
+pub fn encode_bwt_block(data: &[u8], max_code_len: u8) -> Result<Vec<u8>, HuffmanError> {
+    let (ultima, origem) = bwt::transform(data);
+    let simbolos = bwt::mtf_rle(&ultima);
+    let lengths = limited_code_lengths(&contar_frequencias(&simbolos), max_code_len);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 3));
+    bwt::write_symbols(&simbolos, &tabela_de_codigos(&lengths), &mut bits)?;
+    let payload = bits.finish()?;
+
+    let mut out = Vec::with_capacity(payload.len() + 256);
+    write_varint(data.len() as u64, &mut out);
+    write_varint(origem as u64, &mut out);
+    write_code_lengths(&lengths, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//bloco do método de tokens: vocabulario do bloco e uma tabela para bytes
+//literais e tokens. sem `tokens` quebra em palavras e separadores
+pub fn encode_tokens_block(
+    data: &[u8],
+    tokens: Option<&[Vec<u8>]>,
+    max_code_len: u8,
+) -> Result<Vec<u8>, HuffmanError> {
+    let modelo = tokens::model(data, tokens, max_code_len);
+    let codes = canonical_codes(&modelo.lengths);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 3));
+    for s in &modelo.symbols {
+        let (code, len) = codes[s];
+        bits.write_bits(code, len as u32)?;
+    }
+    let payload = bits.finish()?;
+
+    let mut out = Vec::with_capacity(payload.len() + 1024);
+    write_varint(data.len() as u64, &mut out);
+    tokens::write_vocabulary(&modelo.vocabulary, &mut out);
+    write_code_lengths(&modelo.lengths, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//bloco do método de contexto: as tabelas de ordem `order` e o payload
+pub fn encode_context_block(
+    data: &[u8],
+    order: u8,
+    max_code_len: u8,
+) -> Result<Vec<u8>, HuffmanError> {
+    let tabelas = context::model(data, order, max_code_len);
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 2));
+    context::encode(data, &tabelas, &mut bits)?;
+    let payload = bits.finish()?;
+
+    let mut out = Vec::with_capacity(payload.len() + 4096);
+    write_varint(data.len() as u64, &mut out);
+    context::write_tables(&tabelas, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//bloco do método Dictionary: só o payload, com a tabela do dicionario
+pub fn encode_dict_block(data: &[u8], dict: &Dictionary) -> Result<Vec<u8>, HuffmanError> {
+    let simbolos = dict.alphabet().symbols(data)?;
+    let mut bits = BitWriter::new(Vec::with_capacity(data.len() / 2));
+    dict.encode(&simbolos, &mut bits)?;
+    let payload = bits.finish()?;
+
+    let mut out = Vec::with_capacity(payload.len() + 16);
+    write_varint(data.len() as u64, &mut out);
+    write_varint(payload.len() as u64, &mut out);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+//códigos canônicos indexados pelo simbolo, para alfabetos pequenos
+fn tabela_de_codigos(lengths: &[(u32, u8)]) -> Vec<(u64, u8)> {
+    let max = lengths.iter().map(|&(s, _)| s as usize + 1).max().unwrap_or(0);
+    let mut tabela = vec![(0, 0); max];
+    for (sym, code) in canonical_codes(lengths) {
+        tabela[sym as usize] = code;
+    }
+    tabela
+}
+
+pub fn write_end<W: Write>(out: &mut W, crc: u32) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(5);
+    write_varint(0, &mut buf);
+    buf.extend_from_slice(&crc.to_be_bytes());
+    out.write_all(&buf)
+}
+
+//o que vem antes do payload de cada bloco
+#[derive(Debug, Clone)]
+pub struct BlockHeader {
+    pub raw_len: u64,
+    pub lengths: Vec<(u32, u8)>,
+    //só nos blocos LZ77: tabela de distancias
+    pub distances: Option<Vec<(u32, u8)>>,
+    //só nos blocos rANS, que não têm tamanhos de código
+    pub frequencies: Option<Vec<(u32, u32)>>,
+    //só nos blocos BWT: linha do terminador
+    pub origin: Option<u64>,
+    //só no método de tokens: os tokens dos simbolos 256..
+    pub vocabulary: Option<Vec<Vec<u8>>>,
+    //só no método de contexto, que não usa `lengths`
+    pub contexts: Option<context::Tables>,
+    //bloco do método estático que passou pelo RLE
+    pub rle: bool,
+    //só no método Dictionary. read_block_header deixa None; quem lê o
+    //arquivo preenche com o dicionario do id do cabeçalho
+    pub dictionary: Option<Arc<Dictionary>>,
+    pub payload_len: u64,
+}
+
+//lê o cabeçalho do proximo bloco; None no marcador de fim
+pub fn read_block_header<R: Read>(
+    input: &mut R,
+    header: &Header,
+) -> Result<Option<BlockHeader>, HuffmanError> {
+    let raw_len = read_varint(input)?;
+    if raw_len == 0 {
+        return Ok(None);
+    }
+    if header.coder == Coder::Rans || header.method == Method::Dictionary {
+        let frequencies = match header.coder {
+            Coder::Rans => Some(rans::read_frequencies(input)?),
+            Coder::Huffman => None,
+        };
+        let payload_len = read_varint(input)?;
+        return Ok(Some(BlockHeader {
+            raw_len,
+            lengths: Vec::new(),
+            distances: None,
+            frequencies,
+            origin: None,
+            vocabulary: None,
+            contexts: None,
+            rle: false,
+            dictionary: None,
+            payload_len,
+        }));
+    }
+    if header.method == Method::Context {
+        let contexts = context::read_tables(input)?;
+        let payload_len = read_varint(input)?;
+        return Ok(Some(BlockHeader {
+            raw_len,
+            lengths: Vec::new(),
+            distances: None,
+            frequencies: None,
+            origin: None,
+            vocabulary: None,
+            contexts: Some(contexts),
+            rle: false,
+            dictionary: None,
+            payload_len,
+        }));
+    }
+    let rle = header.rle && {
+        let mut b = [0u8; 1];
+        input.read_exact(&mut b)?;
+        match b[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(HuffmanError::Corrupt("byte de RLE invalido")),
+        }
+    };
+    let method = header.method;
+    let origin = if method == Method::Bwt {
+        let origin = read_varint(input)?;
+        if raw_len > bwt::MAX_BLOCK as u64 || origin == 0 || origin > raw_len {
+            return Err(HuffmanError::Corrupt("bloco BWT invalido"));
+        }
+        Some(origin)
+    } else {
+        None
+    };
+    let vocabulary = if method == Method::Tokens {
+        Some(tokens::read_vocabulary(input, raw_len)?)
+    } else {
+        None
+    };
+    let lengths = read_code_lengths(input)?;
+    let maior = match (&vocabulary, method) {
+        (Some(vocabulario), _) => tokens::FIRST_TOKEN - 1 + vocabulario.len() as u32,
+        (None, Method::Bwt) => bwt::MAX_SYMBOL,
+        _ => u32::MAX,
+    };
+    if !lengths_are_valid(&lengths) || lengths.iter().any(|&(s, _)| s > maior) {
+        return Err(HuffmanError::MalformedTree("código de prefixo incompleto ou repetido"));
+    }
+    let distances = if method == Method::Lz77 {
+        let distances = read_code_lengths(input)?;
+        if lengths.iter().any(|&(s, _)| s > lz77::MAX_LITLEN_SYMBOL)
+            || distances.iter().any(|&(s, _)| s > lz77::MAX_DIST_SYMBOL)
+            || !(distances.is_empty() || lengths_are_valid(&distances))
+        {
+            return Err(HuffmanError::MalformedTree("código de prefixo incompleto ou repetido"));
+        }
+        Some(distances)
+    } else {
+        None
+    };
+    let payload_len = read_varint(input)?;
+    Ok(Some(BlockHeader {
+        raw_len,
+        lengths,
+        distances,
+        frequencies: None,
+        origin,
+        vocabulary,
+        contexts: None,
+        rle,
+        dictionary: None,
+        payload_len,
+    }))
+}
+
+//decodifica o payload de um bloco, mandando os bytes para `out`. `payload`
+//precisa entregar exatamente os payload_len bytes do bloco
+pub fn decode_block<R: Read, W: Write>(
+    block: &BlockHeader,
+    payload: R,
+    alphabet: Alphabet,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    let mut payload = payload.take(block.payload_len);
+    if let Some(frequencies) = &block.frequencies {
+        let mut dados = Vec::with_capacity(block.payload_len.min(1 << 24) as usize);
+        payload.read_to_end(&mut dados)?;
+        if dados.len() as u64 != block.payload_len {
+            return Err(HuffmanError::Truncated);
+        }
+        let mut rans = RansDecoder::new(frequencies, &dados)?;
+        decodificar(block.raw_len, alphabet, out, || rans.next_symbol().map(Some))?;
+        return rans.finish();
+    }
+    if let Some(dict) = &block.dictionary {
+        let table = DecodeTable::new(dict.lengths());
+        let mut bits = BitReader::new(&mut payload);
+        decodificar(block.raw_len, alphabet, out, || dict.decode(&table, &mut bits))?;
+        return fim_do_payload(bits, block.payload_len);
+    }
+    if let Some(tabelas) = &block.contexts {
+        let mut bits = BitReader::new(&mut payload);
+        context::decode(block.raw_len, tabelas, &mut bits, out)?;
+        return fim_do_payload(bits, block.payload_len);
+    }
+    if block.lengths.is_empty() {
+        return Err(HuffmanError::Corrupt("bloco sem tabela"));
+    }
+    let table = DecodeTable::new(&block.lengths);
+    let mut bits = BitReader::new(&mut payload);
+    match (&block.distances, block.origin, &block.vocabulary) {
+        (Some(distances), _, _) => {
+            let distances = DecodeTable::new(distances);
+            lz77::decode(block.raw_len, &table, &distances, &mut bits, out)?;
+        }
+        (None, Some(origin), _) => bwt::decode(block.raw_len, origin, &table, &mut bits, out)?,
+        (None, None, Some(vocabulario)) => {
+            tokens::decode(block.raw_len, vocabulario, &table, &mut bits, out)?
+        }
+        (None, None, None) if block.rle => {
+            //RUN repete o simbolo anterior; `pendentes` são as repetições que
+            //ainda não foram entregues
+            let mut anterior = None;
+            let mut pendentes = 0u64;
+            decodificar(block.raw_len, alphabet, out, || -> Result<_, HuffmanError> {
+                if pendentes > 0 {
+                    pendentes -= 1;
+                    return Ok(anterior);
+                }
+                match table.decode(&mut bits)? {
+                    Some(rle::RUN) => {
+                        let Some(n) = rle::read_repeat(&mut bits)? else {
+                            return Ok(None);
+                        };
+                        if anterior.is_none() {
+                            return Err(HuffmanError::Corrupt("repetição no inicio do bloco"));
+                        }
+                        pendentes = n - 1;
+                        Ok(anterior)
+                    }
+                    s => {
+                        anterior = s;
+                        Ok(s)
+                    }
+                }
+            })?;
+            if pendentes > 0 {
+                return Err(HuffmanError::Corrupt("repetição passa do fim do bloco"));
+            }
+        }
+        (None, None, None) => {
+            decodificar(block.raw_len, alphabet, out, || table.decode(&mut bits))?
+        }
+    }
+    fim_do_payload(bits, block.payload_len)
+}
+
+//como decode_block, mas com o CRC por bloco do cabeçalho: `input` entrega
+//o payload seguido do CRC, que é lido e conferido
+pub fn decode_block_checked<R: Read, W: Write>(
+    block: &BlockHeader,
+    mut input: R,
+    header: &Header,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    if !header.block_crc {
+        return decode_block(block, input, header.alphabet, out);
+    }
+    let mut crc = Crc32::new();
+    decode_block(block, &mut input, header.alphabet, &mut CrcWriter { inner: out, crc: &mut crc })?;
+    let mut gravado = [0u8; 4];
+    input.read_exact(&mut gravado)?;
+    let expected = u32::from_be_bytes(gravado);
+    let found = crc.finish();
+    if expected != found {
+        return Err(HuffmanError::ChecksumMismatch { expected, found });
+    }
+    Ok(())
+}
+
+//chama `proximo` até os simbolos somarem `raw_len` bytes, escrevendo em `out`
+fn decodificar<W: Write, E>(
+    raw_len: u64,
+    alphabet: Alphabet,
+    out: &mut W,
+    mut proximo: impl FnMut() -> Result<Option<u32>, E>,
+) -> Result<(), HuffmanError>
+where
+    HuffmanError: From<E>,
+{
+    let mut buf = Vec::with_capacity(1 << 16);
+    if alphabet == Alphabet::Bytes {
+        //um byte por simbolo: sem write_symbol nem contagem de bytes
+        for _ in 0..raw_len {
+            let sym = proximo()?.ok_or(HuffmanError::Corrupt("payload menor que o bloco"))?;
+            buf.push(sym as u8);
+            if buf.len() >= 1 << 16 {
+                out.write_all(&buf)?;
+                buf.clear();
+            }
+        }
+        out.write_all(&buf)?;
+        return Ok(());
+    }
+    let mut produzidos = 0u64;
+    while produzidos < raw_len {
+        let sym = proximo()?.ok_or(HuffmanError::Corrupt("payload menor que o bloco"))?;
+        let antes = buf.len();
+        alphabet.write_symbol(sym, &mut buf)?;
+        produzidos += (buf.len() - antes) as u64;
+        if buf.len() >= 1 << 16 {
+            out.write_all(&buf)?;
+            buf.clear();
+        }
+    }
+    out.write_all(&buf)?;
+    if produzidos != raw_len {
+        return Err(HuffmanError::Corrupt("bloco termina no meio de um simbolo"));
+    }
+    Ok(())
+}
+
+//confere que o decodificador parou no ultimo byte do payload (o resto é só
+//enchimento) e descarta esse enchimento
+fn fim_do_payload<R: Read>(bits: BitReader<R>, payload_len: u64) -> Result<(), HuffmanError> {
+    if bits.bits_read().div_ceil(8) != payload_len {
+        return Err(HuffmanError::Corrupt("tamanho do payload não confere"));
+    }
+    io::copy(&mut bits.into_inner(), &mut io::sink())?;
+    Ok(())
+}
+
+//bloco do método adaptativo: o payload já vem codificado pelo modelo
+pub fn write_adaptive_block<W: Write>(out: &mut W, raw_len: u64, payload: &[u8]) -> io::Result<()> {
+    let mut cabecalho = Vec::with_capacity(20);
+    write_varint(raw_len, &mut cabecalho);
+    write_varint(payload.len() as u64, &mut cabecalho);
+    out.write_all(&cabecalho)?;
+    out.write_all(payload)
+}
+
+//decodifica um bloco adaptativo continuando o modelo dos blocos anteriores.
+//devolve quantos bytes o bloco tinha, ou None no marcador de fim
+pub fn read_adaptive_block<R: Read, W: Write>(
+    input: &mut R,
+    model: &mut AdaptiveModel,
+    alphabet: Alphabet,
+    out: &mut W,
+) -> Result<Option<u64>, HuffmanError> {
+    let raw_len = read_varint(input)?;
+    if raw_len == 0 {
+        return Ok(None);
+    }
+    let payload_len = read_varint(input)?;
+    let mut payload = input.by_ref().take(payload_len);
+    let mut bits = BitReader::new(&mut payload);
+    decodificar(raw_len, alphabet, out, || model.decode(&mut bits))?;
+    fim_do_payload(bits, payload_len)?;
+    Ok(Some(raw_len))
+}
+
+//lê o trailer e confere tamanho e CRC
+pub fn read_end<R: Read>(
+    input: &mut R,
+    header: &Header,
+    total: u64,
+    crc: Crc32,
+) -> Result<(), HuffmanError> {
+    let mut trailer = [0u8; 4];
+    input.read_exact(&mut trailer)?;
+    if header.indexed {
+        let pontos = ler_pontos(input)?;
+        let mut rodape = [0u8; INDEX_FOOTER_LEN];
+        input.read_exact(&mut rodape)?;
+        if rodape[8..] != INDEX_MAGIC {
+            return Err(HuffmanError::Corrupt("indice sem assinatura"));
+        }
+        if pontos.last().map(|p| p.1) != Some(total) {
+            return Err(HuffmanError::Corrupt("indice não confere com os blocos"));
+        }
+    }
+    if input.read(&mut [0u8; 1])? != 0 {
+        return Err(HuffmanError::Corrupt("dados extras depois do trailer"));
+    }
+    if let Some(expected) = header.original_len {
+        if total != expected {
+            return Err(HuffmanError::LengthMismatch { expected, found: total });
+        }
+    }
+    let expected = u32::from_be_bytes(trailer);
+    let found = crc.finish();
+    if expected != found {
+        return Err(HuffmanError::ChecksumMismatch { expected, found });
+    }
+    Ok(())
+}
+
+//indice de busca de um arquivo: pares (posição no compactado, posição no
+//original) no inicio de cada bloco, mais um para o marcador de fim
+#[derive(Debug, Clone)]
+pub struct SeekIndex {
+    pub header: Header,
+    pub points: Vec<(u64, u64)>,
+}
+
+impl SeekIndex {
+    //lê o cabeçalho e o indice no fim do arquivo, sem passar pelos blocos
+    pub fn load<R: Read + Seek>(input: &mut R) -> Result<SeekIndex, HuffmanError> {
+        input.seek(SeekFrom::Start(0))?;
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic).map_err(|_| HuffmanError::UnknownFormat)?;
+        if magic != MAGIC {
+            return Err(HuffmanError::UnknownFormat);
+        }
+        let header = read_header(input)?;
+        if !header.indexed {
+            return Err(HuffmanError::NoIndex);
+        }
+        let fim = input.seek(SeekFrom::End(-(INDEX_FOOTER_LEN as i64)))?;
+        let mut rodape = [0u8; INDEX_FOOTER_LEN];
+        input.read_exact(&mut rodape)?;
+        if rodape[8..] != INDEX_MAGIC {
+            return Err(HuffmanError::Corrupt("indice sem assinatura"));
+        }
+        let inicio = u64::from_be_bytes(rodape[..8].try_into().unwrap());
+        if inicio > fim {
+            return Err(HuffmanError::Corrupt("posição do indice invalida"));
+        }
+        input.seek(SeekFrom::Start(inicio))?;
+        let points = ler_pontos(&mut input.by_ref().take(fim - inicio))?;
+        //o primeiro bloco começa logo depois do cabeçalho, cada bloco ocupa
+        //pelo menos um byte de cada lado e o ultimo ponto (o marcador de fim)
+        //vem antes do CRC e do indice
+        let ultimo = points.last().map_or(0, |p| p.0);
+        if points[0] != (header.encoded_len() as u64, 0)
+            || points.windows(2).any(|par| par[1].0 <= par[0].0 || par[1].1 <= par[0].1)
+            || ultimo.checked_add(5) != Some(inicio)
+        {
+            return Err(HuffmanError::Corrupt("indice não confere com o arquivo"));
+        }
+        Ok(SeekIndex { header, points })
+    }
+
+    //tamanho original total
+    pub fn len(&self) -> u64 {
+        self.points.last().map_or(0, |p| p.1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+//grava o indice depois do trailer. `inicio` é a posição atual no arquivo
+pub fn write_index<W: Write>(out: &mut W, points: &[(u64, u64)], inicio: u64) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(points.len() * 6 + 32);
+    write_varint(points.len() as u64, &mut buf);
+    let mut anterior = (0, 0);
+    for &(compactado, original) in points {
+        write_varint(compactado - anterior.0, &mut buf);
+        write_varint(original - anterior.1, &mut buf);
+        anterior = (compactado, original);
+    }
+    buf.extend_from_slice(&inicio.to_be_bytes());
+    buf.extend_from_slice(&INDEX_MAGIC);
+    out.write_all(&buf)
+}
+
+//pontos do indice, sem o rodapé
+fn ler_pontos<R: Read>(input: &mut R) -> Result<Vec<(u64, u64)>, HuffmanError> {
+    let n = read_varint(input)?;
+    if n == 0 {
+        return Err(HuffmanError::Corrupt("indice vazio"));
+    }
+    let mut pontos = Vec::with_capacity(n.min(1 << 16) as usize);
+    let mut atual = (0u64, 0u64);
+    for _ in 0..n {
+        let compactado = read_varint(input)?;
+        let original = read_varint(input)?;
+        atual = match (atual.0.checked_add(compactado), atual.1.checked_add(original)) {
+            (Some(c), Some(o)) => (c, o),
+            _ => return Err(HuffmanError::Corrupt("indice estoura 64 bits")),
+        };
+        pontos.push(atual);
+    }
+    Ok(pontos)
+}
+
+//atalhos para quem já tem tudo em memoria
+pub fn compress(data: &[u8], alphabet: Alphabet) -> Result<Vec<u8>, HuffmanError> {
+    let mut out = Vec::with_capacity(HEADER_LEN + data.len() / 2);
+    let options = stream::Options { alphabet, ..Default::default() };
+    stream::compress_seekable(io::Cursor::new(data), &mut out, &options)?;
+    Ok(out)
+}
+
+//descompacta qualquer formato reconhecido por detect
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+    let mut out = Vec::new();
+    stream::decompress(data, &mut out)?;
+    Ok(out)
+}
+
+//conta os bytes que passam por um reader
+struct Contador<R: Read> {
+    inner: R,
+    lidos: u64,
+}
+
+impl<R: Read> Read for Contador<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.lidos += n as u64;
+        Ok(n)
+    }
+}
+
+//resumo de um arquivo compactado, sem descompactar
+#[derive(Debug, Clone)]
+pub struct Info {
+    pub format: Format,
+    //só no .huff
+    pub header: Option<Header>,
+    pub blocks: u64,
+    //soma dos tamanhos originais dos blocos (só no .huff)
+    pub original_len: Option<u64>,
+    pub compressed_len: u64,
+    //soma dos payloads dos blocos; o resto de compressed_len é cabeçalho,
+    //tabelas, trailer e indice (só no .huff)
+    pub payload_len: u64,
+}
+
+//percorre os cabeçalhos de bloco pulando os payloads. não confere o CRC;
+//para isso é preciso descompactar (stream::decompress)
+pub fn info<R: Read>(input: R) -> Result<Info, HuffmanError> {
+    let mut input = Contador { inner: io::BufReader::new(input), lidos: 0 };
+    let mut inicio = Vec::with_capacity(4);
+    input.by_ref().take(4).read_to_end(&mut inicio)?;
+    if inicio != MAGIC {
+        input.read_to_end(&mut inicio)?;
+        let format = detect(&inicio).ok_or(HuffmanError::UnknownFormat)?;
+        return Ok(Info {
+            format,
+            header: None,
+            blocks: 0,
+            original_len: None,
+            compressed_len: inicio.len() as u64,
+            payload_len: 0,
+        });
+    }
+
+    let header = read_header(&mut input)?;
+    let mut blocks = 0;
+    let mut total = 0;
+    let mut payloads = 0;
+    loop {
+        let (raw_len, payload_len) = if header.method == Method::Adaptive {
+            let raw_len = read_varint(&mut input)?;
+            if raw_len == 0 {
+                break;
+            }
+            (raw_len, read_varint(&mut input)?)
+        } else {
+            match read_block_header(&mut input, &header)? {
+                Some(block) => (block.raw_len, block.payload_len),
+                None => break,
+            }
+        };
+        let pulados = io::copy(&mut input.by_ref().take(payload_len), &mut io::sink())?;
+        if pulados != payload_len {
+            return Err(HuffmanError::Truncated);
+        }
+        if header.block_crc {
+            input.read_exact(&mut [0u8; 4])?;
+        }
+        blocks += 1;
+        total += raw_len;
+        payloads += pulados;
+    }
+    input.read_exact(&mut [0u8; 4])?;
+    io::copy(&mut input, &mut io::sink())?;
+    Ok(Info {
+        format: Format::Huff,
+        header: Some(header),
+        blocks,
+        original_len: Some(total),
+        compressed_len: input.lidos,
+        payload_len: payloads,
+    })
+}
@@ -0,0 +1,228 @@
+//Huffman de ordem 1 e 2: uma tabela por contexto em vez de uma para o
+//bloco todo, o que pega o 'u' depois do 'q' que a ordem 0 não vê. na ordem
+//1 o contexto é o byte anterior; na ordem 2 é um hash dos dois anteriores
+//em HASH_BITS bits. o começo do bloco conta como precedido de zeros, então
+//cada bloco continua independente.
+//
+//um contexto só ganha tabela propria quando ela paga o que custa no bloco;
+//os outros (os esparsos) dividem a tabela de ordem 0, montada só com os
+//simbolos deles. assim um bloco pequeno ou sem estrutura não fica maior do
+//que ficaria com o método estático por causa de centenas de tabelas.
+//
+//tabelas gravadas no bloco:
+//  u8      ordem (1 ou 2)
+//          tabela de ordem 0 (huffman::write_code_lengths), vazia quando
+//          todos os contextos usados têm tabela
+//  varint  quantidade de contextos com tabela
+//          cada um, em ordem crescente: varint com a distancia para o
+//          contexto anterior (como os simbolos de write_code_lengths) e a
+//          tabela dele
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::HuffmanError;
+use crate::huffman::{
+    canonical_codes, lengths_are_valid, limited_code_lengths, read_code_lengths, read_varint,
+    write_code_lengths, write_varint, DecodeTable,
+};
+
+pub const MAX_ORDER: u8 = 2;
+//bits do hash dos dois bytes anteriores na ordem 2
+pub const HASH_BITS: u32 = 12;
+
+//quantos contextos existem na ordem dada
+pub fn contexts(order: u8) -> usize {
+    match order {
+        1 => 256,
+        _ => 1 << HASH_BITS,
+    }
+}
+
+//contexto do proximo byte, dados o anterior e o de antes dele
+fn contexto(order: u8, anterior: u8, antes: u8) -> usize {
+    match order {
+        1 => anterior as usize,
+        _ => {
+            let par = (antes as u32) << 8 | anterior as u32;
+            (par.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+        }
+    }
+}
+
+//contexto de cada posição de `data`
+fn contextos(data: &[u8], order: u8) -> impl Iterator<Item = (usize, u8)> + '_ {
+    let (mut anterior, mut antes) = (0u8, 0u8);
+    data.iter().map(move |&b| {
+        let ctx = contexto(order, anterior, antes);
+        antes = anterior;
+        anterior = b;
+        (ctx, b)
+    })
+}
+
+//tabelas de um bloco
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tables {
+    pub order: u8,
+    //tabela dos contextos sem tabela propria
+    pub fallback: Vec<(u32, u8)>,
+    //(contexto, tamanhos de código), em ordem crescente de contexto
+    pub contexts: Vec<(usize, Vec<(u32, u8)>)>,
+}
+
+//bits para codificar `freqs` com os tamanhos dados
+fn custo(freqs: &HashMap<u32, u64>, lengths: &[(u32, u8)]) -> u64 {
+    lengths.iter().map(|(s, len)| freqs[s] * *len as u64).sum()
+}
+
+//escolhe quais contextos de `data` ganham tabela propria
+pub fn model(data: &[u8], order: u8, max_code_len: u8) -> Tables {
+    let mut por_contexto: Vec<HashMap<u32, u64>> = vec![HashMap::new(); contexts(order)];
+    let mut todos: HashMap<u32, u64> = HashMap::new();
+    for (ctx, b) in contextos(data, order) {
+        *por_contexto[ctx].entry(b as u32).or_insert(0) += 1;
+        *todos.entry(b as u32).or_insert(0) += 1;
+    }
+    let ordem0 = limited_code_lengths(&todos, max_code_len);
+    let tamanho0: HashMap<u32, u8> = ordem0.iter().copied().collect();
+
+    let mut tabelas = Vec::new();
+    let mut esparsos: HashMap<u32, u64> = HashMap::new();
+    for (ctx, freqs) in por_contexto.iter().enumerate() {
+        if freqs.is_empty() {
+            continue;
+        }
+        let propria = limited_code_lengths(freqs, max_code_len);
+        let mut tabela = Vec::new();
+        write_varint(ctx as u64, &mut tabela);
+        write_code_lengths(&propria, &mut tabela);
+        let com_tabela = custo(freqs, &propria) + 8 * tabela.len() as u64;
+        let sem_tabela: u64 = freqs.iter().map(|(s, f)| f * tamanho0[s] as u64).sum();
+        if com_tabela < sem_tabela {
+            tabelas.push((ctx, propria));
+        } else {
+            for (&s, &f) in freqs {
+                *esparsos.entry(s).or_insert(0) += f;
+            }
+        }
+    }
+    let fallback = limited_code_lengths(&esparsos, max_code_len);
+    Tables { order, fallback, contexts: tabelas }
+}
+
+pub fn write_tables(tables: &Tables, out: &mut Vec<u8>) {
+    out.push(tables.order);
+    write_code_lengths(&tables.fallback, out);
+    write_varint(tables.contexts.len() as u64, out);
+    let mut anterior = 0usize;
+    for (i, (ctx, lengths)) in tables.contexts.iter().enumerate() {
+        let delta = if i == 0 { *ctx } else { ctx - anterior - 1 };
+        write_varint(delta as u64, out);
+        write_code_lengths(lengths, out);
+        anterior = *ctx;
+    }
+}
+
+//tabela de bytes válida: vazia só quando `pode_ser_vazia`
+fn tabela_valida(lengths: &[(u32, u8)], pode_ser_vazia: bool) -> bool {
+    if lengths.is_empty() {
+        return pode_ser_vazia;
+    }
+    lengths_are_valid(lengths) && lengths.iter().all(|&(s, _)| s <= 0xFF)
+}
+
+pub fn read_tables<R: Read>(input: &mut R) -> Result<Tables, HuffmanError> {
+    let mut order = [0u8; 1];
+    input.read_exact(&mut order)?;
+    let order = order[0];
+    if order == 0 || order > MAX_ORDER {
+        return Err(HuffmanError::Corrupt("ordem de contexto invalida"));
+    }
+    let fallback = read_code_lengths(input)?;
+    if !tabela_valida(&fallback, true) {
+        return Err(HuffmanError::MalformedTree("código de prefixo incompleto ou repetido"));
+    }
+    let n = read_varint(input)?;
+    if n > contexts(order) as u64 {
+        return Err(HuffmanError::Corrupt("contextos demais"));
+    }
+    let mut tabelas: Vec<(usize, Vec<(u32, u8)>)> = Vec::with_capacity(n as usize);
+    for _ in 0..n {
+        let delta = read_varint(input)?;
+        let ctx = match tabelas.last() {
+            Some((anterior, _)) => (*anterior as u64 + 1).saturating_add(delta),
+            None => delta,
+        };
+        if ctx >= contexts(order) as u64 {
+            return Err(HuffmanError::Corrupt("contexto fora da ordem"));
+        }
+        let lengths = read_code_lengths(input)?;
+        if !tabela_valida(&lengths, false) {
+            return Err(HuffmanError::MalformedTree("código de prefixo incompleto ou repetido"));
+        }
+        tabelas.push((ctx as usize, lengths));
+    }
+    Ok(Tables { order, fallback, contexts: tabelas })
+}
+
+//códigos indexados pelo byte
+fn codigos(lengths: &[(u32, u8)]) -> Vec<(u64, u8)> {
+    let mut tabela = vec![(0, 0); 256];
+    for (sym, code) in canonical_codes(lengths) {
+        tabela[sym as usize] = code;
+    }
+    tabela
+}
+
+pub fn encode<W: Write>(
+    data: &[u8],
+    tables: &Tables,
+    bits: &mut BitWriter<W>,
+) -> Result<(), HuffmanError> {
+    let fallback = codigos(&tables.fallback);
+    let mut por_contexto: Vec<Option<Vec<(u64, u8)>>> = vec![None; contexts(tables.order)];
+    for (ctx, lengths) in &tables.contexts {
+        por_contexto[*ctx] = Some(codigos(lengths));
+    }
+    for (ctx, b) in contextos(data, tables.order) {
+        let (code, len) = por_contexto[ctx].as_ref().unwrap_or(&fallback)[b as usize];
+        bits.write_bits(code, len as u32)?;
+    }
+    Ok(())
+}
+
+//decodifica `raw_len` bytes escolhendo a tabela pelo contexto de cada um
+pub fn decode<R: Read, W: Write>(
+    raw_len: u64,
+    tables: &Tables,
+    bits: &mut BitReader<R>,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    let fallback = (!tables.fallback.is_empty()).then(|| DecodeTable::new(&tables.fallback));
+    let mut indice: Vec<Option<usize>> = vec![None; contexts(tables.order)];
+    let mut decodificadores = Vec::with_capacity(tables.contexts.len());
+    for (i, (ctx, lengths)) in tables.contexts.iter().enumerate() {
+        indice[*ctx] = Some(i);
+        decodificadores.push(DecodeTable::new(lengths));
+    }
+    let mut buf = Vec::with_capacity(raw_len.min(1 << 16) as usize);
+    let (mut anterior, mut antes) = (0u8, 0u8);
+    for _ in 0..raw_len {
+        let ctx = contexto(tables.order, anterior, antes);
+        let table = match indice[ctx] {
+            Some(i) => &decodificadores[i],
+            None => fallback.as_ref().ok_or(HuffmanError::Corrupt("contexto sem tabela"))?,
+        };
+        let b = table.decode(bits)?.ok_or(HuffmanError::Corrupt("payload menor que o bloco"))?;
+        antes = anterior;
+        anterior = b as u8;
+        buf.push(anterior);
+        if buf.len() >= 1 << 16 {
+            out.write_all(&buf)?;
+            buf.clear();
+        }
+    }
+    out.write_all(&buf)?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//CRC-32 (IEEE 802.3, polinomio refletido 0xEDB88320), o mesmo do gzip e do zip
+use std::io::{self, Write};
+
+//tabelas do slicing-by-8: TABELAS[0] é a tabela classica de um byte e
+//TABELAS[k][b] é o CRC de b seguido de k bytes zero, o que deixa processar
+//8 bytes por volta com 8 consultas independentes
+const fn gerar_tabelas() -> [[u32; 256]; 8] {
+    let mut tabelas = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        tabelas[0][i] = c;
+        i += 1;
+    }
+    let mut t = 1;
+    while t < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let c = tabelas[t - 1][i];
+            tabelas[t][i] = tabelas[0][(c & 0xFF) as usize] ^ (c >> 8);
+            i += 1;
+        }
+        t += 1;
+    }
+    tabelas
+}
+
+static TABELAS: [[u32; 256]; 8] = gerar_tabelas();
+
+//acumulador incremental, para quando os dados chegam em pedaços
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32(u32);
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Crc32(0xFFFF_FFFF)
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        let t = &TABELAS;
+        let mut c = self.0;
+        let mut palavras = data.chunks_exact(8);
+        for p in &mut palavras {
+            let a = c ^ u32::from_le_bytes([p[0], p[1], p[2], p[3]]);
+            c = t[7][(a & 0xFF) as usize]
+                ^ t[6][((a >> 8) & 0xFF) as usize]
+                ^ t[5][((a >> 16) & 0xFF) as usize]
+                ^ t[4][(a >> 24) as usize]
+                ^ t[3][p[4] as usize]
+                ^ t[2][p[5] as usize]
+                ^ t[1][p[6] as usize]
+                ^ t[0][p[7] as usize];
+        }
+        for &b in palavras.remainder() {
+            c = t[0][((c ^ b as u32) & 0xFF) as usize] ^ (c >> 8);
+        }
+        self.0 = c;
+    }
+
+    pub fn finish(self) -> u32 {
+        self.0 ^ 0xFFFF_FFFF
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//repassa o que é escrito para `inner` atualizando o CRC no caminho
+pub struct CrcWriter<'a, W: Write> {
+    pub inner: &'a mut W,
+    pub crc: &'a mut Crc32,
+}
+
+impl<W: Write> Write for CrcWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+//matriz 32x32 sobre GF(2) (uma coluna por bit) vezes o vetor `v`
+fn vezes(matriz: &[u32; 32], mut v: u32) -> u32 {
+    let mut soma = 0;
+    let mut i = 0;
+    while v != 0 {
+        if v & 1 != 0 {
+            soma ^= matriz[i];
+        }
+        v >>= 1;
+        i += 1;
+    }
+    soma
+}
+
+fn quadrado(matriz: &[u32; 32]) -> [u32; 32] {
+    let mut out = [0u32; 32];
+    for (o, &coluna) in out.iter_mut().zip(matriz) {
+        *o = vezes(matriz, coluna);
+    }
+    out
+}
+
+//CRC de A seguido de B a partir do CRC de A, do CRC de B e do tamanho de
+//B, sem olhar os dados (o crc32_combine do zlib): passar `len_b` bytes zero
+//pelo CRC de A é aplicar a matriz de um byte zero `len_b` vezes, feito com
+//quadrados sucessivos
+pub fn combine(crc_a: u32, crc_b: u32, len_b: u64) -> u32 {
+    if len_b == 0 {
+        return crc_a;
+    }
+    //um bit zero: desloca e aplica o polinomio
+    let mut impar = [0u32; 32];
+    impar[0] = 0xEDB8_8320;
+    for (i, linha) in impar.iter_mut().enumerate().skip(1) {
+        *linha = 1 << (i - 1);
+    }
+    let par = quadrado(&impar);
+    let mut impar = quadrado(&par);
+    let mut crc = crc_a;
+    let mut n = len_b;
+    loop {
+        //a primeira volta chega a 8 bits zero, um byte
+        let par = quadrado(&impar);
+        if n & 1 != 0 {
+            crc = vezes(&par, crc);
+        }
+        n >>= 1;
+        if n == 0 {
+            break;
+        }
+        impar = quadrado(&par);
+        if n & 1 != 0 {
+            crc = vezes(&impar, crc);
+        }
+        n >>= 1;
+        if n == 0 {
+            break;
+        }
+    }
+    crc ^ crc_b
+}
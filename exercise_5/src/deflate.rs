@@ -0,0 +1,729 @@
+//DEFLATE (RFC 1951) dentro dos envelopes gzip (RFC 1952) ou zlib (RFC 1950),
+//para trocar arquivos com o gzip e o zlib do sistema.
+//
+//o compactador usa o mesmo match finder do lz77.rs com janela de 32 KiB e
+//escolhe para cada bloco o menor entre armazenado, Huffman fixo e Huffman
+//dinamico. as tabelas dinamicas saem do limited_code_lengths, ou seja, da
+//arvore de Huffman e do build_codebook, limitadas a 15 bits (7 na tabela
+//dos tamanhos de código)
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use crate::bits::{LsbReader, LsbWriter};
+use crate::error::HuffmanError;
+use crate::crc32::Crc32;
+use crate::huffman::{canonical_codes, limited_code_lengths};
+use crate::lz77::{self, Token};
+
+pub const WINDOW: usize = 32 * 1024;
+//bytes novos lidos por vez; cada pedaço é tokenizado junto com os ultimos
+//32 KiB do anterior
+const CHUNK: usize = 256 * 1024;
+//tokens por bloco DEFLATE
+const TOKENS_PER_BLOCK: usize = 1 << 15;
+const END_OF_BLOCK: u32 = 256;
+const MAX_STORED: usize = 65535;
+//ordem em que os tamanhos da tabela de tamanhos de código são gravados
+const ORDEM_CL: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const GZIP_FHCRC: u8 = 0b0000_0010;
+const GZIP_FEXTRA: u8 = 0b0000_0100;
+const GZIP_FNAME: u8 = 0b0000_1000;
+const GZIP_FCOMMENT: u8 = 0b0001_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrapper {
+    Gzip,
+    Zlib,
+}
+
+//reconhece o inicio de um arquivo gzip ou zlib. o zlib não tem magic, só
+//dois bytes com método 8 e um checksum de 5 bits, então um arquivo antigo
+//do compactador.rs com 2048+ simbolos distintos pode, raramente, parecer zlib
+pub fn detect(data: &[u8]) -> Option<Wrapper> {
+    match *data {
+        [0x1f, 0x8b, ..] => Some(Wrapper::Gzip),
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && (cmf as u16 * 256 + flg as u16).is_multiple_of(31)
+                && flg & 0x20 == 0 =>
+        {
+            Some(Wrapper::Zlib)
+        }
+        _ => None,
+    }
+}
+
+//Adler-32 do trailer do zlib
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    fn new() -> Self {
+        Adler32 { a: 1, b: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        //5552 é o maior pedaço em que as somas não estouram antes do modulo
+        for pedaco in data.chunks(5552) {
+            for &byte in pedaco {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= 65521;
+            self.b %= 65521;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+//checksum do envelope: CRC-32 no gzip, Adler-32 no zlib
+enum Checksum {
+    Crc(Crc32),
+    Adler(Adler32),
+}
+
+impl Checksum {
+    fn new(wrapper: Wrapper) -> Self {
+        match wrapper {
+            Wrapper::Gzip => Checksum::Crc(Crc32::new()),
+            Wrapper::Zlib => Checksum::Adler(Adler32::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Checksum::Crc(c) => c.update(data),
+            Checksum::Adler(a) => a.update(data),
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        match self {
+            Checksum::Crc(c) => c.finish(),
+            Checksum::Adler(a) => a.finish(),
+        }
+    }
+}
+
+//tamanhos das tabelas fixas
+fn fixed_litlen() -> Vec<u8> {
+    (0..288).map(|s| match s { 0..=143 => 8, 144..=255 => 9, 256..=279 => 7, _ => 8 }).collect()
+}
+
+fn fixed_dist() -> Vec<u8> {
+    vec![5; 30]
+}
+
+//tamanhos de código para um alfabeto de `n` simbolos. o DEFLATE não aceita
+//código de tamanho 0, então um alfabeto com um simbolo só ganha um vizinho
+fn tamanhos(freqs: &HashMap<u32, u64>, max_len: u8, n: usize) -> Vec<u8> {
+    let mut freqs = freqs.clone();
+    for s in 0..2 {
+        if freqs.len() < 2 {
+            freqs.entry(s).or_insert(0);
+        }
+    }
+    let mut out = vec![0u8; n];
+    for (sym, len) in limited_code_lengths(&freqs, max_len) {
+        out[sym as usize] = len;
+    }
+    out
+}
+
+//códigos canônicos já invertidos para a ordem do DEFLATE
+fn codigos(lens: &[u8]) -> Vec<(u64, u8)> {
+    let lengths: Vec<(u32, u8)> =
+        lens.iter().enumerate().filter(|&(_, &l)| l > 0).map(|(s, &l)| (s as u32, l)).collect();
+    let mut out = vec![(0, 0); lens.len()];
+    for (sym, (code, len)) in canonical_codes(&lengths) {
+        out[sym as usize] = (code.reverse_bits() >> (64 - len as u32), len);
+    }
+    out
+}
+
+//run-length dos tamanhos com os simbolos 16 (repete o anterior 3-6 vezes),
+//17 (3-10 zeros) e 18 (11-138 zeros). devolve (simbolo, valor dos extras)
+fn rle_tamanhos(lens: &[u8]) -> Vec<(u8, u8)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lens.len() {
+        let len = lens[i];
+        let mut run = 1;
+        while i + run < lens.len() && lens[i + run] == len {
+            run += 1;
+        }
+        i += run;
+        if len == 0 {
+            while run >= 11 {
+                let n = run.min(138);
+                out.push((18, (n - 11) as u8));
+                run -= n;
+            }
+            if run >= 3 {
+                out.push((17, (run - 3) as u8));
+                run = 0;
+            }
+        } else {
+            out.push((len, 0));
+            run -= 1;
+            while run >= 3 {
+                let n = run.min(6);
+                out.push((16, (n - 3) as u8));
+                run -= n;
+            }
+        }
+        for _ in 0..run {
+            out.push((len, 0));
+        }
+    }
+    out
+}
+
+fn bits_extras_cl(sym: u8) -> u32 {
+    match sym {
+        16 => 2,
+        17 => 3,
+        18 => 7,
+        _ => 0,
+    }
+}
+
+//cabeçalho de um bloco dinamico, pronto para ser escrito
+struct Dinamico {
+    litlen: Vec<u8>,
+    dist: Vec<u8>,
+    cl: Vec<u8>,
+    rle: Vec<(u8, u8)>,
+    hclen: usize,
+}
+
+impl Dinamico {
+    fn new(litlen_freqs: &HashMap<u32, u64>, dist_freqs: &HashMap<u32, u64>) -> Self {
+        let mut litlen = tamanhos(litlen_freqs, 15, 286);
+        let mut dist = tamanhos(dist_freqs, 15, 30);
+        while litlen.len() > 257 && litlen[litlen.len() - 1] == 0 {
+            litlen.pop();
+        }
+        while dist.len() > 1 && dist[dist.len() - 1] == 0 {
+            dist.pop();
+        }
+        let mut todos = litlen.clone();
+        todos.extend_from_slice(&dist);
+        let rle = rle_tamanhos(&todos);
+        let mut cl_freqs = HashMap::new();
+        for &(sym, _) in &rle {
+            *cl_freqs.entry(sym as u32).or_insert(0) += 1;
+        }
+        let cl = tamanhos(&cl_freqs, 7, 19);
+        let mut hclen = 19;
+        while hclen > 4 && cl[ORDEM_CL[hclen - 1]] == 0 {
+            hclen -= 1;
+        }
+        Dinamico { litlen, dist, cl, rle, hclen }
+    }
+
+    fn custo(&self) -> u64 {
+        let rle: u64 =
+            self.rle.iter().map(|&(s, _)| self.cl[s as usize] as u64 + bits_extras_cl(s) as u64).sum();
+        14 + 3 * self.hclen as u64 + rle
+    }
+
+    fn write<W: Write>(&self, bits: &mut LsbWriter<W>) -> io::Result<()> {
+        bits.write_bits((self.litlen.len() - 257) as u64, 5)?;
+        bits.write_bits((self.dist.len() - 1) as u64, 5)?;
+        bits.write_bits((self.hclen - 4) as u64, 4)?;
+        for &i in &ORDEM_CL[..self.hclen] {
+            bits.write_bits(self.cl[i] as u64, 3)?;
+        }
+        let codes = codigos(&self.cl);
+        for &(sym, extra) in &self.rle {
+            let (code, len) = codes[sym as usize];
+            bits.write_bits(code, len as u32)?;
+            bits.write_bits(extra as u64, bits_extras_cl(sym))?;
+        }
+        Ok(())
+    }
+}
+
+//bits dos tokens com as tabelas dadas, sem contar cabeçalho
+fn custo_tokens(
+    litlen_freqs: &HashMap<u32, u64>,
+    dist_freqs: &HashMap<u32, u64>,
+    litlen: &[u8],
+    dist: &[u8],
+) -> u64 {
+    let mut total = 0;
+    for (&s, &f) in litlen_freqs {
+        let extra = lz77::length_base(s).map_or(0, |(_, e)| e);
+        total += f * (litlen[s as usize] as u64 + extra as u64);
+    }
+    for (&s, &f) in dist_freqs {
+        let extra = lz77::distance_base(s).map_or(0, |(_, e)| e);
+        total += f * (dist[s as usize] as u64 + extra as u64);
+    }
+    total
+}
+
+fn escrever_tokens<W: Write>(
+    bits: &mut LsbWriter<W>,
+    tokens: &[Token],
+    litlen: &[(u64, u8)],
+    dist: &[(u64, u8)],
+) -> io::Result<()> {
+    for &token in tokens {
+        match token {
+            Token::Literal(b) => {
+                let (code, n) = litlen[b as usize];
+                bits.write_bits(code, n as u32)?;
+            }
+            Token::Match { len, dist: d } => {
+                let (sym, extra, valor) = lz77::length_code(len as usize);
+                let (code, n) = litlen[sym as usize];
+                bits.write_bits(code, n as u32)?;
+                bits.write_bits(valor as u64, extra)?;
+                let (sym, extra, valor) = lz77::distance_code(d as usize);
+                let (code, n) = dist[sym as usize];
+                bits.write_bits(code, n as u32)?;
+                bits.write_bits(valor as u64, extra)?;
+            }
+        }
+    }
+    let (code, n) = litlen[END_OF_BLOCK as usize];
+    bits.write_bits(code, n as u32)
+}
+
+//escreve um bloco DEFLATE com os tokens que cobrem `raw`, no formato que
+//sair menor
+fn escrever_bloco<W: Write>(
+    bits: &mut LsbWriter<W>,
+    tokens: &[Token],
+    raw: &[u8],
+    ultimo: bool,
+) -> io::Result<()> {
+    let mut litlen_freqs = HashMap::new();
+    let mut dist_freqs = HashMap::new();
+    litlen_freqs.insert(END_OF_BLOCK, 1);
+    for &token in tokens {
+        let (litlen, dist) = lz77::symbols(token);
+        *litlen_freqs.entry(litlen).or_insert(0) += 1;
+        if let Some(dist) = dist {
+            *dist_freqs.entry(dist).or_insert(0) += 1;
+        }
+    }
+    let dinamico = Dinamico::new(&litlen_freqs, &dist_freqs);
+    let custo_dinamico =
+        dinamico.custo() + custo_tokens(&litlen_freqs, &dist_freqs, &dinamico.litlen, &dinamico.dist);
+    let custo_fixo = custo_tokens(&litlen_freqs, &dist_freqs, &fixed_litlen(), &fixed_dist());
+    let custo_armazenado = 8 * (raw.len() as u64 + 5 * raw.len().div_ceil(MAX_STORED).max(1) as u64);
+
+    if custo_armazenado <= custo_dinamico.min(custo_fixo) {
+        let mut pedacos: Vec<&[u8]> = raw.chunks(MAX_STORED).collect();
+        if pedacos.is_empty() {
+            pedacos.push(&[]);
+        }
+        let n = pedacos.len();
+        for (i, pedaco) in pedacos.into_iter().enumerate() {
+            bits.write_bits((ultimo && i + 1 == n) as u64, 1)?;
+            bits.write_bits(0, 2)?;
+            bits.align()?;
+            let len = pedaco.len() as u16;
+            bits.write_bytes(&len.to_le_bytes())?;
+            bits.write_bytes(&(!len).to_le_bytes())?;
+            bits.write_bytes(pedaco)?;
+        }
+        return Ok(());
+    }
+    bits.write_bits(ultimo as u64, 1)?;
+    if custo_fixo <= custo_dinamico {
+        bits.write_bits(1, 2)?;
+        escrever_tokens(bits, tokens, &codigos(&fixed_litlen()), &codigos(&fixed_dist()))
+    } else {
+        bits.write_bits(2, 2)?;
+        dinamico.write(bits)?;
+        escrever_tokens(bits, tokens, &codigos(&dinamico.litlen), &codigos(&dinamico.dist))
+    }
+}
+
+fn token_len(token: &Token) -> usize {
+    match *token {
+        Token::Literal(_) => 1,
+        Token::Match { len, .. } => len as usize,
+    }
+}
+
+//compacta `input` num arquivo gzip ou zlib. `level` vai de 0 a 9 como no
+//gzip. devolve quantos bytes foram lidos
+pub fn compress<R: Read, W: Write>(
+    input: R,
+    output: W,
+    wrapper: Wrapper,
+    level: u8,
+) -> Result<u64, HuffmanError> {
+    let mut input = BufReader::new(input);
+    let mut bits = LsbWriter::new(BufWriter::new(output));
+    match wrapper {
+        Wrapper::Gzip => {
+            //sem nome nem data; XFL 2 = compactação maxima, 4 = mais rapida
+            let xfl = match level {
+                9 => 2,
+                0 | 1 => 4,
+                _ => 0,
+            };
+            bits.write_bytes(&[0x1f, 0x8b, 8, 0, 0, 0, 0, 0, xfl, 255])?;
+        }
+        Wrapper::Zlib => {
+            let flevel = match level {
+                0 | 1 => 0,
+                2..=5 => 1,
+                6 => 2,
+                _ => 3,
+            };
+            let cmf = 0x78u16;
+            let mut flg = flevel << 6;
+            flg += 31 - (cmf * 256 + flg) % 31;
+            bits.write_bytes(&[cmf as u8, flg as u8])?;
+        }
+    }
+
+    let mut checksum = Checksum::new(wrapper);
+    let mut total = 0u64;
+    let mut buf: Vec<u8> = Vec::with_capacity(WINDOW + CHUNK);
+    loop {
+        //guarda só a janela do pedaço anterior
+        let historico = buf.len().min(WINDOW);
+        buf.drain(..buf.len() - historico);
+        let lidos = input.by_ref().take(CHUNK as u64).read_to_end(&mut buf)?;
+        let fim = lidos < CHUNK;
+        checksum.update(&buf[historico..]);
+        total += lidos as u64;
+
+        let tokens = lz77::tokenize_from(&buf, historico, WINDOW, level);
+        let mut pos = historico;
+        let mut blocos = tokens.chunks(TOKENS_PER_BLOCK).peekable();
+        if blocos.peek().is_none() && fim {
+            escrever_bloco(&mut bits, &[], &[], true)?;
+        }
+        while let Some(bloco) = blocos.next() {
+            let cobertos: usize = bloco.iter().map(token_len).sum();
+            let ultimo = fim && blocos.peek().is_none();
+            escrever_bloco(&mut bits, bloco, &buf[pos..pos + cobertos], ultimo)?;
+            pos += cobertos;
+        }
+        if fim {
+            break;
+        }
+    }
+
+    let trailer = match wrapper {
+        Wrapper::Gzip => {
+            let mut t = checksum.finish().to_le_bytes().to_vec();
+            t.extend_from_slice(&(total as u32).to_le_bytes());
+            t
+        }
+        Wrapper::Zlib => checksum.finish().to_be_bytes().to_vec(),
+    };
+    bits.align()?;
+    bits.write_bytes(&trailer)?;
+    bits.finish()?.flush()?;
+    Ok(total)
+}
+
+//tabela de decodificação indexada pelos proximos `bits` bits na ordem do
+//DEFLATE: (simbolo, tamanho), com tamanho 0 para sequencias sem código
+struct Tabela {
+    bits: u32,
+    entradas: Vec<(u16, u8)>,
+}
+
+impl Tabela {
+    fn new(lens: &[u8]) -> Result<Tabela, HuffmanError> {
+        let max = lens.iter().copied().max().unwrap_or(0) as u32;
+        let mut kraft = 0u64;
+        for &l in lens.iter().filter(|&&l| l > 0) {
+            kraft += 1 << (15 - l);
+        }
+        //códigos incompletos são aceitos (um só código de distancia, por
+        //exemplo), mas não códigos com prefixos repetidos
+        if kraft > 1 << 15 {
+            return Err(HuffmanError::MalformedTree("tamanhos de código invalidos"));
+        }
+        let mut tabela = Tabela { bits: max, entradas: vec![(0, 0); 1 << max] };
+        for (sym, (code, len)) in codigos(lens).into_iter().enumerate() {
+            if len > 0 {
+                let mut i = code as usize;
+                while i < tabela.entradas.len() {
+                    tabela.entradas[i] = (sym as u16, len);
+                    i += 1 << len;
+                }
+            }
+        }
+        Ok(tabela)
+    }
+
+    fn decode<R: Read>(&self, bits: &mut LsbReader<R>) -> Result<u32, HuffmanError> {
+        let (v, disponiveis) = bits.peek(self.bits)?;
+        let (sym, len) = self.entradas[v as usize];
+        if len == 0 {
+            return Err(if disponiveis < self.bits {
+                HuffmanError::Truncated
+            } else {
+                HuffmanError::Corrupt("código inexistente")
+            });
+        }
+        if len as u32 > disponiveis {
+            return Err(HuffmanError::Truncated);
+        }
+        bits.consume(len as u32);
+        Ok(sym as u32)
+    }
+}
+
+//lê as tabelas de um bloco dinamico
+fn ler_dinamico<R: Read>(bits: &mut LsbReader<R>) -> Result<(Tabela, Tabela), HuffmanError> {
+    let hlit = bits.read_bits(5)? as usize + 257;
+    let hdist = bits.read_bits(5)? as usize + 1;
+    let hclen = bits.read_bits(4)? as usize + 4;
+    if hlit > 286 || hdist > 30 {
+        return Err(HuffmanError::MalformedTree("tamanhos de código invalidos"));
+    }
+    let mut cl = [0u8; 19];
+    for &i in &ORDEM_CL[..hclen] {
+        cl[i] = bits.read_bits(3)? as u8;
+    }
+    let cl = Tabela::new(&cl)?;
+    let mut lens = Vec::with_capacity(hlit + hdist);
+    while lens.len() < hlit + hdist {
+        let sym = cl.decode(bits)?;
+        let (valor, vezes) = match sym {
+            0..=15 => (sym as u8, 1),
+            16 => {
+                let anterior =
+                    *lens.last().ok_or(HuffmanError::Corrupt("repetição sem tamanho anterior"))?;
+                (anterior, 3 + bits.read_bits(2)? as usize)
+            }
+            17 => (0, 3 + bits.read_bits(3)? as usize),
+            _ => (0, 11 + bits.read_bits(7)? as usize),
+        };
+        if lens.len() + vezes > hlit + hdist {
+            return Err(HuffmanError::MalformedTree("tamanhos de código invalidos"));
+        }
+        lens.extend(std::iter::repeat_n(valor, vezes));
+    }
+    if lens[END_OF_BLOCK as usize] == 0 {
+        return Err(HuffmanError::Corrupt("bloco sem fim de bloco"));
+    }
+    Ok((Tabela::new(&lens[..hlit])?, Tabela::new(&lens[hlit..])?))
+}
+
+//descarrega tudo menos a janela quando o buffer passa de 3 janelas
+fn descarregar<W: Write>(
+    buf: &mut Vec<u8>,
+    out: &mut W,
+    checksum: &mut Checksum,
+    total: &mut u64,
+) -> io::Result<()> {
+    if buf.len() >= 3 * WINDOW {
+        let n = buf.len() - WINDOW;
+        out.write_all(&buf[..n])?;
+        checksum.update(&buf[..n]);
+        *total += n as u64;
+        buf.drain(..n);
+    }
+    Ok(())
+}
+
+//descompacta um stream DEFLATE, mandando os bytes para `out` e para o
+//checksum. devolve quantos bytes saíram
+fn inflate<R: Read, W: Write>(
+    bits: &mut LsbReader<R>,
+    out: &mut W,
+    checksum: &mut Checksum,
+) -> Result<u64, HuffmanError> {
+    let mut buf: Vec<u8> = Vec::with_capacity(4 * WINDOW);
+    let mut total = 0u64;
+    let mut ultimo = false;
+    while !ultimo {
+        ultimo = bits.read_bits(1)? == 1;
+        let (litlen, dist) = match bits.read_bits(2)? {
+            0 => {
+                bits.align();
+                let mut cabecalho = [0u8; 4];
+                bits.read_bytes(&mut cabecalho)?;
+                let len = u16::from_le_bytes([cabecalho[0], cabecalho[1]]);
+                if len != !u16::from_le_bytes([cabecalho[2], cabecalho[3]]) {
+                    return Err(HuffmanError::Corrupt("bloco armazenado com tamanho invalido"));
+                }
+                let inicio = buf.len();
+                buf.resize(inicio + len as usize, 0);
+                bits.read_bytes(&mut buf[inicio..])?;
+                descarregar(&mut buf, out, checksum, &mut total)?;
+                continue;
+            }
+            1 => (Tabela::new(&fixed_litlen())?, Tabela::new(&fixed_dist())?),
+            2 => ler_dinamico(bits)?,
+            _ => return Err(HuffmanError::Corrupt("tipo de bloco invalido")),
+        };
+        loop {
+            let sym = litlen.decode(bits)?;
+            if sym < 256 {
+                buf.push(sym as u8);
+            } else if sym == END_OF_BLOCK {
+                break;
+            } else {
+                let (base, extra) =
+                    lz77::length_base(sym).ok_or(HuffmanError::Corrupt("comprimento invalido"))?;
+                let len = base + bits.read_bits(extra)? as usize;
+                let sym = dist.decode(bits)?;
+                let (base, extra) = lz77::distance_base(sym)
+                    .filter(|_| sym < 30)
+                    .ok_or(HuffmanError::Corrupt("distancia invalida"))?;
+                let d = base + bits.read_bits(extra)? as usize;
+                if d > buf.len() {
+                    return Err(HuffmanError::Corrupt("referencia antes do inicio"));
+                }
+                let inicio = buf.len() - d;
+                for k in 0..len {
+                    buf.push(buf[inicio + k]);
+                }
+            }
+            descarregar(&mut buf, out, checksum, &mut total)?;
+        }
+    }
+    out.write_all(&buf)?;
+    checksum.update(&buf);
+    Ok(total + buf.len() as u64)
+}
+
+fn ler_u32_le<R: Read>(bits: &mut LsbReader<R>) -> Result<u32, HuffmanError> {
+    let mut b = [0u8; 4];
+    bits.read_bytes(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+//pula os campos opcionais do cabeçalho gzip, depois dos dois bytes do magic
+fn ler_cabecalho_gzip<R: Read>(bits: &mut LsbReader<R>) -> Result<(), HuffmanError> {
+    campos_gzip(bits).map_err(|e| match e {
+        HuffmanError::Truncated => HuffmanError::TruncatedHeader,
+        e => e,
+    })
+}
+
+fn campos_gzip<R: Read>(bits: &mut LsbReader<R>) -> Result<(), HuffmanError> {
+    let mut fixo = [0u8; 8];
+    bits.read_bytes(&mut fixo)?;
+    let [metodo, flags, ..] = fixo;
+    if metodo != 8 {
+        return Err(HuffmanError::Corrupt("método do gzip não é DEFLATE"));
+    }
+    if flags & 0b1110_0000 != 0 {
+        return Err(HuffmanError::UnknownFlags(flags));
+    }
+    let mut byte = [0u8; 1];
+    if flags & GZIP_FEXTRA != 0 {
+        let mut len = [0u8; 2];
+        bits.read_bytes(&mut len)?;
+        let mut extra = vec![0u8; u16::from_le_bytes(len) as usize];
+        bits.read_bytes(&mut extra)?;
+    }
+    for flag in [GZIP_FNAME, GZIP_FCOMMENT] {
+        if flags & flag != 0 {
+            loop {
+                bits.read_bytes(&mut byte)?;
+                if byte[0] == 0 {
+                    break;
+                }
+            }
+        }
+    }
+    if flags & GZIP_FHCRC != 0 {
+        bits.read_bytes(&mut [0u8; 2])?;
+    }
+    Ok(())
+}
+
+//um membro gzip depois do magic: cabeçalho, dados e trailer
+fn membro_gzip<R: Read, W: Write>(bits: &mut LsbReader<R>, out: &mut W) -> Result<u64, HuffmanError> {
+    ler_cabecalho_gzip(bits)?;
+    let mut checksum = Checksum::new(Wrapper::Gzip);
+    let n = inflate(bits, out, &mut checksum)?;
+    bits.align();
+    let expected = ler_u32_le(bits)?;
+    let found = checksum.finish();
+    if expected != found {
+        return Err(HuffmanError::ChecksumMismatch { expected, found });
+    }
+    //ISIZE é o tamanho modulo 2^32
+    let isize = ler_u32_le(bits)?;
+    if isize != n as u32 {
+        return Err(HuffmanError::LengthMismatch { expected: isize as u64, found: n });
+    }
+    Ok(n)
+}
+
+//descompacta um arquivo gzip (inclusive com varios membros concatenados,
+//como o gzip aceita) ou zlib. devolve quantos bytes foram escritos
+pub fn decompress<R: Read, W: Write>(
+    input: R,
+    output: W,
+    wrapper: Wrapper,
+) -> Result<u64, HuffmanError> {
+    let mut bits = LsbReader::new(BufReader::new(input));
+    let mut output = BufWriter::new(output);
+    let mut total = 0u64;
+    match wrapper {
+        Wrapper::Gzip => {
+            let mut primeiro = true;
+            loop {
+                //depois do primeiro membro pode vir outro ou o fim do arquivo
+                let mut magic = [0u8; 2];
+                let mut lidos = 0;
+                if !primeiro {
+                    lidos = bits.read_some(&mut magic[..1])?;
+                    if lidos == 0 {
+                        break;
+                    }
+                }
+                bits.read_bytes(&mut magic[lidos..])?;
+                if magic != GZIP_MAGIC {
+                    return Err(if primeiro {
+                        HuffmanError::UnknownFormat
+                    } else {
+                        HuffmanError::Corrupt("dados extras depois do trailer")
+                    });
+                }
+                total += membro_gzip(&mut bits, &mut output)?;
+                primeiro = false;
+            }
+        }
+        Wrapper::Zlib => {
+            let mut cabecalho = [0u8; 2];
+            bits.read_bytes(&mut cabecalho)?;
+            if detect(&cabecalho) != Some(Wrapper::Zlib) {
+                return Err(HuffmanError::UnknownFormat);
+            }
+            let mut checksum = Checksum::new(wrapper);
+            total = inflate(&mut bits, &mut output, &mut checksum)?;
+            bits.align();
+            let mut trailer = [0u8; 4];
+            bits.read_bytes(&mut trailer)?;
+            let expected = u32::from_be_bytes(trailer);
+            let found = checksum.finish();
+            if expected != found {
+                return Err(HuffmanError::ChecksumMismatch { expected, found });
+            }
+            if bits.read_some(&mut [0u8; 1])? != 0 {
+                return Err(HuffmanError::Corrupt("dados extras depois do trailer"));
+            }
+        }
+    }
+    output.flush()?;
+    Ok(total)
+}
@@ -0,0 +1,175 @@
+//dicionarios: uma tabela de Huffman treinada com um corpus de amostras e
+//guardada num arquivo à parte. mensagens pequenas compactadas com ela
+//(Method::Dictionary) só guardam o id do dicionario no cabeçalho, sem
+//tabela nenhuma. simbolos que não apareceram no treino são escritos como o
+//código de ESCAPE seguido do simbolo cru (8 bits no alfabeto de bytes, 21
+//no de chars), como o NYT do adaptativo.
+//
+//arquivo do dicionario:
+//  0..4   magic b"HUD\x1a"
+//  4      versão (1)
+//  5      flags: bit 0 = alfabeto de chars
+//         tabela canônica de tamanhos de código (huffman::write_code_lengths),
+//         com ESCAPE entre os simbolos
+//  u32    id, big-endian: CRC-32 das flags e da tabela
+//
+//o id depende só do conteudo, então dois treinos iguais dão o mesmo id e o
+//descompactador confere que recebeu o dicionario certo
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::crc32::crc32;
+use crate::error::HuffmanError;
+use crate::huffman::{
+    canonical_codes, lengths_are_valid, limited_code_lengths, read_code_lengths,
+    write_code_lengths, Alphabet, DecodeTable, MAX_SYMBOLS,
+};
+
+pub const MAGIC: [u8; 4] = *b"HUD\x1a";
+pub const VERSION: u8 = 1;
+//simbolo extra, fora de qualquer alfabeto
+pub const ESCAPE: u32 = MAX_SYMBOLS as u32;
+const FLAG_CHARS: u8 = 0b0000_0001;
+
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    id: u32,
+    alphabet: Alphabet,
+    lengths: Vec<(u32, u8)>,
+    codes: HashMap<u32, (u64, u8)>,
+}
+
+//bits do simbolo cru depois de um ESCAPE
+fn bits_cru(alphabet: Alphabet) -> u32 {
+    match alphabet {
+        Alphabet::Bytes => 8,
+        Alphabet::Chars => 21,
+    }
+}
+
+impl Dictionary {
+    //conta os simbolos de todas as amostras e monta a tabela. o ESCAPE
+    //recebe o peso dos simbolos vistos uma vez só (mais 1), uma estimativa
+    //de quanto aparecem simbolos novos
+    pub fn train<I>(samples: I, alphabet: Alphabet, max_code_len: u8) -> Result<Self, HuffmanError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut freqs: HashMap<u32, u64> = HashMap::new();
+        for sample in samples {
+            for s in alphabet.symbols(sample.as_ref())? {
+                *freqs.entry(s).or_insert(0) += 1;
+            }
+        }
+        let raros = freqs.values().filter(|&&f| f == 1).count() as u64;
+        freqs.insert(ESCAPE, raros + 1);
+        Dictionary::from_lengths(alphabet, limited_code_lengths(&freqs, max_code_len))
+    }
+
+    //dicionario a partir de uma tabela pronta, que precisa ter o ESCAPE
+    pub fn from_lengths(alphabet: Alphabet, lengths: Vec<(u32, u8)>) -> Result<Self, HuffmanError> {
+        let maximo = match alphabet {
+            Alphabet::Bytes => 0xFF,
+            Alphabet::Chars => ESCAPE - 1,
+        };
+        if !lengths_are_valid(&lengths)
+            || !lengths.iter().any(|&(s, _)| s == ESCAPE)
+            || lengths.iter().any(|&(s, _)| s > maximo && s != ESCAPE)
+        {
+            return Err(HuffmanError::MalformedTree("tabela do dicionario invalida"));
+        }
+        let mut lengths = lengths;
+        lengths.sort_unstable();
+        let mut dict = Dictionary { id: 0, alphabet, codes: canonical_codes(&lengths), lengths };
+        dict.id = crc32(&dict.corpo());
+        Ok(dict)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn alphabet(&self) -> Alphabet {
+        self.alphabet
+    }
+
+    pub fn lengths(&self) -> &[(u32, u8)] {
+        &self.lengths
+    }
+
+    //flags e tabela, a parte coberta pelo id
+    fn corpo(&self) -> Vec<u8> {
+        let mut out = vec![if self.alphabet == Alphabet::Chars { FLAG_CHARS } else { 0 }];
+        write_code_lengths(&self.lengths, &mut out);
+        out
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let mut buf = MAGIC.to_vec();
+        buf.push(VERSION);
+        buf.extend_from_slice(&self.corpo());
+        buf.extend_from_slice(&self.id.to_be_bytes());
+        out.write_all(&buf)
+    }
+
+    pub fn read<R: Read>(mut input: R) -> Result<Self, HuffmanError> {
+        let mut cabecalho = [0u8; 6];
+        input.read_exact(&mut cabecalho).map_err(|_| HuffmanError::UnknownFormat)?;
+        if cabecalho[..4] != MAGIC {
+            return Err(HuffmanError::UnknownFormat);
+        }
+        if cabecalho[4] != VERSION {
+            return Err(HuffmanError::UnsupportedVersion(cabecalho[4]));
+        }
+        let flags = cabecalho[5];
+        if flags & !FLAG_CHARS != 0 {
+            return Err(HuffmanError::UnknownFlags(flags));
+        }
+        let alphabet = if flags & FLAG_CHARS != 0 { Alphabet::Chars } else { Alphabet::Bytes };
+        let dict = Dictionary::from_lengths(alphabet, read_code_lengths(&mut input)?)?;
+        let mut id = [0u8; 4];
+        input.read_exact(&mut id)?;
+        let expected = u32::from_be_bytes(id);
+        if expected != dict.id {
+            return Err(HuffmanError::ChecksumMismatch { expected, found: dict.id });
+        }
+        Ok(dict)
+    }
+
+    //escreve os simbolos, com escape para os que não estão na tabela
+    pub fn encode<W: Write>(&self, simbolos: &[u32], bits: &mut BitWriter<W>) -> io::Result<()> {
+        let (escape, escape_len) = self.codes[&ESCAPE];
+        for s in simbolos {
+            match self.codes.get(s) {
+                Some(&(code, len)) => bits.write_bits(code, len as u32)?,
+                None => {
+                    bits.write_bits(escape, escape_len as u32)?;
+                    bits.write_bits(*s as u64, bits_cru(self.alphabet))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    //lê o proximo simbolo resolvendo os escapes; None se os dados acabarem
+    pub fn decode<R: Read>(
+        &self,
+        table: &DecodeTable,
+        bits: &mut BitReader<R>,
+    ) -> io::Result<Option<u32>> {
+        match table.decode(bits)? {
+            Some(ESCAPE) => {
+                let n = bits_cru(self.alphabet);
+                let (v, disponiveis) = bits.peek(n)?;
+                if disponiveis < n {
+                    return Ok(None);
+                }
+                bits.consume(n);
+                Ok(Some(v as u32))
+            }
+            outro => Ok(outro),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//erros do codec. tudo que lê dados de fora devolve isso em vez de entrar
+//em pânico, então um arquivo ruim vira só um Err
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum HuffmanError {
+    Io(io::Error),
+    //o arquivo acabou dentro do cabeçalho
+    TruncatedHeader,
+    //o arquivo acabou depois do cabeçalho (num bloco ou no trailer)
+    Truncated,
+    //não é um .huff, gzip, zlib nem um dos formatos antigos
+    UnknownFormat,
+    UnsupportedVersion(u8),
+    UnknownFlags(u8),
+    //simbolo decodificado no alfabeto de chars que não é um code point
+    InvalidCodePoint(u32),
+    //entrada do alfabeto de chars que não é UTF-8
+    InvalidUtf8,
+    //tabela de tamanhos de código (ou arvore) que não forma um código de
+    //prefixo valido
+    MalformedTree(&'static str),
+    //outras inconsistencias internas (payload, referencias, trailer...)
+    Corrupt(&'static str),
+    LengthMismatch { expected: u64, found: u64 },
+    ChecksumMismatch { expected: u32, found: u32 },
+    //arquivo compactado com um dicionario (pelo id) que não foi fornecido
+    MissingDictionary(u32),
+    //erro ao ler ou decodificar um bloco: qual bloco (contando do 0) e onde
+    //ele começa no original
+    BadBlock { block: u64, offset: u64, error: Box<HuffmanError> },
+    //acesso aleatório num arquivo gravado sem indice de busca
+    NoIndex,
+    //membro pedido que não está no arquivo de arquivos
+    NotFound(String),
+}
+
+impl fmt::Display for HuffmanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HuffmanError::Io(e) => write!(f, "erro de E/S: {}", e),
+            HuffmanError::TruncatedHeader => write!(f, "cabeçalho truncado"),
+            HuffmanError::Truncated => write!(f, "arquivo truncado"),
+            HuffmanError::UnknownFormat => write!(f, "formato desconhecido"),
+            HuffmanError::UnsupportedVersion(v) => write!(f, "versão {} não suportada", v),
+            HuffmanError::UnknownFlags(flags) => write!(f, "flags desconhecidas: {:#04x}", flags),
+            HuffmanError::InvalidCodePoint(sym) => write!(f, "code point invalido: {:#x}", sym),
+            HuffmanError::InvalidUtf8 => write!(f, "a entrada não é UTF-8 valido, use o alfabeto de bytes"),
+            HuffmanError::MalformedTree(msg) => write!(f, "tabela de códigos invalida: {}", msg),
+            HuffmanError::Corrupt(msg) => write!(f, "arquivo corrompido: {}", msg),
+            HuffmanError::LengthMismatch { expected, found } => write!(
+                f,
+                "tamanho incorreto: esperado {} bytes, obtido {}",
+                expected, found
+            ),
+            HuffmanError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum incorreto: esperado {:08x}, calculado {:08x}",
+                expected, found
+            ),
+            HuffmanError::MissingDictionary(id) => {
+                write!(f, "o arquivo precisa do dicionario {:08x}", id)
+            }
+            HuffmanError::BadBlock { block, offset, error } => {
+                write!(f, "bloco {} (byte {} do original): {}", block, offset, error)
+            }
+            HuffmanError::NoIndex => write!(f, "o arquivo não tem indice de busca"),
+            HuffmanError::NotFound(path) => write!(f, "membro não encontrado: {}", path),
+        }
+    }
+}
+
+impl std::error::Error for HuffmanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HuffmanError::Io(e) => Some(e),
+            HuffmanError::BadBlock { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HuffmanError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => HuffmanError::Truncated,
+            _ => HuffmanError::Io(e),
+        }
+    }
+}
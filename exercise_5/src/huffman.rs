@@ -0,0 +1,486 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::io::{self, Read};
+
+use crate::bits::BitReader;
+use crate::error::HuffmanError;
+
+//maior alfabeto possivel: todos os code points
+pub const MAX_SYMBOLS: u64 = 0x11_0000;
+
+//alfabeto sobre o qual a tabela de frequencias e a arvore são construidas.
+//em Bytes cada simbolo é um u8 (0..=255), então qualquer arquivo binario
+//faz o round-trip; em Chars cada simbolo é um code point e a entrada precisa
+//ser UTF-8 valido
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    Bytes,
+    Chars,
+}
+
+impl Alphabet {
+    //converte o conteudo do arquivo na sequencia de simbolos do alfabeto
+    pub fn symbols(self, data: &[u8]) -> Result<Vec<u32>, HuffmanError> {
+        match self {
+            Alphabet::Bytes => Ok(data.iter().map(|&b| b as u32).collect()),
+            Alphabet::Chars => {
+                let texto = std::str::from_utf8(data).map_err(|_| HuffmanError::InvalidUtf8)?;
+                Ok(texto.chars().map(|c| c as u32).collect())
+            }
+        }
+    }
+
+    //escreve o simbolo decodificado de volta como bytes
+    pub fn write_symbol(self, sym: u32, out: &mut Vec<u8>) -> Result<(), HuffmanError> {
+        match self {
+            Alphabet::Bytes => out.push(sym as u8),
+            Alphabet::Chars => {
+                let c = char::from_u32(sym).ok_or(HuffmanError::InvalidCodePoint(sym))?;
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+//o que um tipo precisa para ser simbolo: a ordem desempata a arvore e
+//define os códigos canônicos, o hash indexa as tabelas. vale para qualquer
+//tipo com esses traits (u16, enums, strings internadas...); para gravar a
+//tabela veja symbol.rs
+pub trait Symbol: Clone + Ord + Hash {}
+
+impl<T: Clone + Ord + Hash> Symbol for T {}
+
+//arvore de Huffman sobre qualquer tipo de simbolo. os alfabetos do .huff
+//usam u32 (bytes e code points); o modo de tokens (tokens.rs) monta a
+//arvore direto sobre as palavras
+#[derive(Debug, Clone)]
+pub enum Huffman<S = u32> {
+    Leaf { sym: S, freq: u64 },
+    Node { freq: u64, left: Box<Huffman<S>>, right: Box<Huffman<S>> },
+}
+
+//wrapper para implementar os traits de ordenação. o segundo campo é a ordem
+//de criação do nó e desempata frequencias iguais, assim a arvore não depende
+//da ordem de iteração do HashMap nem dos detalhes internos do BinaryHeap
+#[derive(Debug)]
+struct HeapNode<S>(Huffman<S>, usize);
+
+impl<S> PartialEq for HeapNode<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<S> Eq for HeapNode<S> {}
+
+impl<S> PartialOrd for HeapNode<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<S> Ord for HeapNode<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // min-heap
+        other
+            .0
+            .freq()
+            .cmp(&self.0.freq())
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+//conta quantas vezes cada simbolo aparece
+pub fn contar_frequencias<S: Symbol>(simbolos: &[S]) -> HashMap<S, u64> {
+    let mut mapa = HashMap::new();
+    for s in simbolos {
+        *mapa.entry(s.clone()).or_insert(0) += 1;
+    }
+    mapa
+}
+
+impl Huffman {
+    //build_tree para os alfabetos de u32 do .huff. fica separado de
+    //from_frequencies para `Huffman::build_tree(&HashMap::new())` continuar
+    //inferindo o tipo do simbolo
+    pub fn build_tree(freqs: &HashMap<u32, u64>) -> Option<Huffman> {
+        Huffman::from_frequencies(freqs)
+    }
+}
+
+impl<S> Huffman<S> {
+    //retorna a frequencia assiciada a um nó
+    pub fn freq(&self) -> u64 {
+        match self {
+            Huffman::Leaf { freq, .. } => *freq,
+            Huffman::Node { freq, .. } => *freq,
+        }
+    }
+}
+
+impl<S: Symbol> Huffman<S> {
+    //controi a arvore usando os simbolos e suas frequencias. None quando não
+    //há simbolos (entrada vazia); com um simbolo só a arvore é uma folha
+    pub fn from_frequencies(freqs: &HashMap<S, u64>) -> Option<Huffman<S>> {
+        let mut heap = BinaryHeap::new();
+
+        let mut folhas: Vec<(S, u64)> = freqs.iter().map(|(s, &f)| (s.clone(), f)).collect();
+        folhas.sort_unstable();
+        let mut ordem = 0;
+        for (sym, freq) in folhas {
+            heap.push(HeapNode(Huffman::Leaf { sym, freq }, ordem));
+            ordem += 1;
+        }
+
+        while heap.len() > 1 {
+            let left = heap.pop().unwrap().0;
+            let right = heap.pop().unwrap().0;
+            let freq = left.freq() + right.freq();
+            heap.push(HeapNode(
+                Huffman::Node {
+                    freq,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+                ordem,
+            ));
+            ordem += 1;
+        }
+
+        heap.pop().map(|no| no.0)
+    }
+    //constroi o codebook (tabela de códigos binários) para cada simbolo com base na árvore.
+    pub fn build_codebook(&self, prefix: Vec<bool>, codebook: &mut HashMap<S, Vec<bool>>) {
+        match self {
+            Huffman::Leaf { sym, .. } => {
+                codebook.insert(sym.clone(), prefix);
+            }
+            Huffman::Node { left, right, .. } => {
+                //esquerda -> false
+                let mut left_prefix = prefix.clone();
+                left_prefix.push(false);
+                left.build_codebook(left_prefix, codebook);
+                //direita -> true
+                let mut right_prefix = prefix;
+                right_prefix.push(true);
+                right.build_codebook(right_prefix, codebook);
+            }
+        }
+    }
+    //profundidade de cada folha, ou seja, o tamanho do código de cada simbolo.
+    //é só isso que o cabeçalho canônico precisa guardar. uma arvore que é
+    //só uma folha dá tamanho 0, o caso especial de um simbolo só
+    pub fn code_lengths(&self) -> Vec<(S, u8)> {
+        let mut codebook = HashMap::new();
+        self.build_codebook(Vec::new(), &mut codebook);
+        let mut out: Vec<(S, u8)> = codebook
+            .into_iter()
+            .map(|(sym, code)| (sym, code.len() as u8))
+            .collect();
+        out.sort_unstable();
+        out
+    }
+    //le os bits e percorre a arvore em busca do nó folha (simbolo).
+    //devolve None se os bits acabarem no meio de um código
+    pub fn decode<R: Read>(&self, bits: &mut BitReader<R>) -> io::Result<Option<S>> {
+        let mut atual = self;
+        loop {
+            match atual {
+                Huffman::Leaf { sym, .. } => return Ok(Some(sym.clone())),
+                Huffman::Node { left, right, .. } => match bits.read_bit()? {
+                    Some(false) => atual = left,
+                    Some(true) => atual = right,
+                    None => return Ok(None),
+                },
+            }
+        }
+    }
+}
+
+//atribui os códigos canônicos: simbolos ordenados por (tamanho, simbolo)
+//recebem códigos consecutivos, de modo que os tamanhos bastam para
+//reconstruir exatamente a mesma tabela no descompactador.
+//devolve (código, tamanho) com o código nos bits menos significativos
+pub fn canonical_codes<S: Symbol>(lengths: &[(S, u8)]) -> HashMap<S, (u64, u8)> {
+    let mut ordenados: Vec<(u8, S)> = lengths.iter().map(|(s, l)| (*l, s.clone())).collect();
+    ordenados.sort_unstable();
+
+    let mut codes = HashMap::with_capacity(ordenados.len());
+    let mut code: u64 = 0;
+    let mut anterior = 0u8;
+    for (i, (len, sym)) in ordenados.into_iter().enumerate() {
+        if i > 0 {
+            code = (code + 1) << (len - anterior);
+        } else {
+            code <<= len;
+        }
+        anterior = len;
+        codes.insert(sym, (code, len));
+    }
+    codes
+}
+
+//confere se os tamanhos formam um código de prefixo completo (soma de Kraft
+//igual a 1), ou o caso especial de um único simbolo com tamanho 0
+pub fn lengths_are_valid<S>(lengths: &[(S, u8)]) -> bool {
+    if let [(_, 0)] = lengths {
+        return true;
+    }
+    let mut kraft: u128 = 0;
+    for &(_, len) in lengths {
+        if len == 0 || len > 63 {
+            return false;
+        }
+        kraft += 1u128 << (64 - len);
+    }
+    kraft == 1u128 << 64
+}
+
+//limite padrão para o tamanho dos códigos, o mesmo do DEFLATE
+pub const DEFAULT_MAX_CODE_LEN: u8 = 15;
+
+//item do package-merge: uma folha ou um pacote formado por dois itens da
+//lista anterior
+enum Item {
+    Folha(usize),
+    Pacote(usize, usize),
+}
+
+//tamanhos de código otimos com nenhum código maior que `max_len` bits. se a
+//arvore de Huffman comum já respeita o limite ela é usada; senão os tamanhos
+//saem do algoritmo package-merge. o limite sobe sozinho quando não cabe
+//todo o alfabeto (2^max_len < quantidade de simbolos)
+pub fn limited_code_lengths(freqs: &HashMap<u32, u64>, max_len: u8) -> Vec<(u32, u8)> {
+    limited_code_lengths_of(freqs, max_len)
+}
+
+//limited_code_lengths para qualquer tipo de simbolo
+pub fn limited_code_lengths_of<S: Symbol>(
+    freqs: &HashMap<S, u64>,
+    max_len: u8,
+) -> Vec<(S, u8)> {
+    let Some(tree) = Huffman::from_frequencies(freqs) else {
+        return Vec::new();
+    };
+    let lengths = tree.code_lengths();
+    let n = lengths.len();
+    let minimo = (usize::BITS - (n - 1).leading_zeros()) as u8;
+    let max_len = max_len.max(minimo).max(1);
+    if lengths.iter().all(|&(_, l)| l <= max_len) {
+        return lengths;
+    }
+
+    //folhas em ordem crescente de peso, desempatando pelo simbolo
+    let mut folhas: Vec<(u64, S)> = freqs.iter().map(|(s, &f)| (f, s.clone())).collect();
+    folhas.sort_unstable();
+
+    let mut itens: Vec<Item> = (0..n).map(Item::Folha).collect();
+    let mut pesos: Vec<u64> = folhas.iter().map(|&(f, _)| f).collect();
+    let base: Vec<usize> = (0..n).collect();
+    let mut lista = base.clone();
+    for _ in 1..max_len {
+        //empacota os itens dois a dois e intercala com as folhas
+        let mut pacotes = Vec::with_capacity(lista.len() / 2);
+        for par in lista.chunks_exact(2) {
+            itens.push(Item::Pacote(par[0], par[1]));
+            pesos.push(pesos[par[0]] + pesos[par[1]]);
+            pacotes.push(itens.len() - 1);
+        }
+        let mut nova = Vec::with_capacity(n + pacotes.len());
+        let (mut i, mut j) = (0, 0);
+        while i < base.len() || j < pacotes.len() {
+            if j >= pacotes.len() || (i < base.len() && pesos[base[i]] <= pesos[pacotes[j]]) {
+                nova.push(base[i]);
+                i += 1;
+            } else {
+                nova.push(pacotes[j]);
+                j += 1;
+            }
+        }
+        lista = nova;
+    }
+
+    //cada vez que uma folha aparece entre os 2n-2 primeiros itens o seu
+    //código cresce um bit
+    let mut tamanhos = vec![0u8; n];
+    let mut pilha: Vec<usize> = lista[..2 * n - 2].to_vec();
+    while let Some(item) = pilha.pop() {
+        match itens[item] {
+            Item::Folha(f) => tamanhos[f] += 1,
+            Item::Pacote(a, b) => {
+                pilha.push(a);
+                pilha.push(b);
+            }
+        }
+    }
+    let mut out: Vec<(S, u8)> =
+        folhas.into_iter().zip(tamanhos).map(|((_, s), l)| (s, l)).collect();
+    out.sort_unstable();
+    out
+}
+
+//bits olhados de uma vez pelo decodificador de tabela
+pub const TABLE_BITS: u32 = 10;
+
+//decodificador de códigos canônicos por tabela: olha TABLE_BITS bits de uma
+//vez e resolve simbolo e tamanho numa consulta só. códigos maiores que a
+//tabela caem no decodificador canônico bit a bit
+pub struct DecodeTable {
+    bits: u32,
+    //indexada pelos proximos `bits` bits: (simbolo, tamanho do código), com
+    //tamanho 0 para prefixos de códigos longos
+    primary: Vec<(u32, u8)>,
+    //por tamanho: primeiro código canônico, quantos códigos e onde começam
+    //em `sorted`
+    first_code: Vec<u64>,
+    count: Vec<u64>,
+    offset: Vec<usize>,
+    sorted: Vec<u32>,
+    //bloco com um único simbolo (código de tamanho 0)
+    unico: Option<u32>,
+}
+
+impl DecodeTable {
+    //os tamanhos precisam ter passado por lengths_are_valid
+    pub fn new(lengths: &[(u32, u8)]) -> DecodeTable {
+        let max_len = lengths.iter().map(|&(_, l)| l as usize).max().unwrap_or(0);
+        let bits = (max_len as u32).min(TABLE_BITS);
+        let mut table = DecodeTable {
+            bits,
+            primary: vec![(0, 0); 1 << bits],
+            first_code: vec![0; max_len + 1],
+            count: vec![0; max_len + 1],
+            offset: vec![0; max_len + 1],
+            sorted: Vec::with_capacity(lengths.len()),
+            unico: None,
+        };
+        if let [(sym, 0)] = lengths {
+            table.unico = Some(*sym);
+            return table;
+        }
+
+        let mut ordenados: Vec<(u8, u32)> = lengths.iter().map(|&(s, l)| (l, s)).collect();
+        ordenados.sort_unstable();
+        for &(len, sym) in &ordenados {
+            table.count[len as usize] += 1;
+            table.sorted.push(sym);
+        }
+        let mut code = 0u64;
+        let mut inicio = 0usize;
+        for len in 1..=max_len {
+            code = (code + table.count[len - 1]) << 1;
+            table.first_code[len] = code;
+            table.offset[len] = inicio;
+            inicio += table.count[len] as usize;
+        }
+
+        for (sym, (code, len)) in canonical_codes(lengths) {
+            if (len as u32) <= bits {
+                let livres = bits - len as u32;
+                let base = (code << livres) as usize;
+                for e in &mut table.primary[base..base + (1 << livres)] {
+                    *e = (sym, len);
+                }
+            }
+        }
+        table
+    }
+
+    //decodifica um simbolo; None se os dados acabarem no meio de um código
+    pub fn decode<R: Read>(&self, bits: &mut BitReader<R>) -> io::Result<Option<u32>> {
+        if let Some(sym) = self.unico {
+            return Ok(Some(sym));
+        }
+        let (v, disponiveis) = bits.peek(self.bits)?;
+        let (sym, len) = self.primary[v as usize];
+        if len > 0 {
+            if len as u32 > disponiveis {
+                return Ok(None);
+            }
+            bits.consume(len as u32);
+            return Ok(Some(sym));
+        }
+
+        //código maior que a tabela: continua bit a bit pelos códigos canônicos
+        let mut code = 0u64;
+        for len in 1..self.count.len() {
+            code = match bits.read_bit()? {
+                Some(bit) => (code << 1) | bit as u64,
+                None => return Ok(None),
+            };
+            let indice = code.wrapping_sub(self.first_code[len]);
+            if indice < self.count[len] {
+                return Ok(Some(self.sorted[self.offset[len] + indice as usize]));
+            }
+        }
+        Ok(None)
+    }
+}
+
+//inteiro sem sinal em LEB128: 7 bits por byte, bit alto indica continuação
+pub fn write_varint(mut v: u64, out: &mut Vec<u8>) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+pub fn read_varint<R: Read + ?Sized>(input: &mut R) -> Result<u64, HuffmanError> {
+    let mut v = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        //o 10o byte só tem lugar para o bit 63
+        if shift >= 64 || (shift == 63 && byte[0] > 1) {
+            return Err(HuffmanError::Corrupt("varint muito longo"));
+        }
+        v |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(v);
+        }
+        shift += 7;
+    }
+}
+
+//cabeçalho canônico: quantidade de simbolos e, para cada simbolo em ordem
+//crescente, a distancia para o simbolo anterior (varint) seguida do tamanho
+//do código (1 byte). um texto ASCII pequeno custa ~2 bytes por simbolo
+pub fn write_code_lengths(lengths: &[(u32, u8)], out: &mut Vec<u8>) {
+    write_varint(lengths.len() as u64, out);
+    let mut anterior = 0u32;
+    for (i, &(sym, len)) in lengths.iter().enumerate() {
+        let delta = if i == 0 { sym } else { sym - anterior - 1 };
+        write_varint(delta as u64, out);
+        out.push(len);
+        anterior = sym;
+    }
+}
+
+pub fn read_code_lengths<R: Read>(input: &mut R) -> Result<Vec<(u32, u8)>, HuffmanError> {
+    let n = read_varint(input)?;
+    if n > MAX_SYMBOLS {
+        return Err(HuffmanError::MalformedTree("tabela com simbolos demais"));
+    }
+    let mut lengths = Vec::with_capacity(n as usize);
+    let mut anterior = 0u64;
+    for i in 0..n {
+        let delta = read_varint(input)?;
+        let sym = if i == 0 {
+            Some(delta)
+        } else {
+            anterior.checked_add(delta).and_then(|s| s.checked_add(1))
+        };
+        let sym = match sym {
+            Some(sym) if sym <= u32::MAX as u64 => sym,
+            _ => return Err(HuffmanError::MalformedTree("simbolo fora do alfabeto")),
+        };
+        let mut len = [0u8; 1];
+        input.read_exact(&mut len)?;
+        lengths.push((sym as u32, len[0]));
+        anterior = sym;
+    }
+    Ok(lengths)
+}
@@ -0,0 +1,169 @@
+//leitura dos formatos gravados pelas versões antigas, que não tinham magic
+//nem versão. como não há assinatura, cada formato só é aceito se o tamanho
+//do arquivo bate exatamente com o que o cabeçalho descreve
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::error::HuffmanError;
+use crate::bits::BitReader;
+use crate::huffman::Huffman;
+
+//a arvore antiga tem no maximo 256 folhas
+const MAX_PROFUNDIDADE: usize = 256;
+
+fn ler_u32(data: &[u8], pos: usize) -> Option<u32> {
+    let b = data.get(pos..pos + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+//main.rs: [u32 tamanho da arvore][u32 bits][arvore][payload], com as folhas
+//gravadas como `c as u8`
+fn desserializar(data: &[u8], pos: &mut usize, prof: usize) -> Option<Huffman> {
+    if prof > MAX_PROFUNDIDADE {
+        return None;
+    }
+    let tag = *data.get(*pos)?;
+    *pos += 1;
+    match tag {
+        1 => {
+            let c = *data.get(*pos)?;
+            *pos += 1;
+            Some(Huffman::Leaf { sym: c as u32, freq: 0 })
+        }
+        0 => {
+            let left = Box::new(desserializar(data, pos, prof + 1)?);
+            let right = Box::new(desserializar(data, pos, prof + 1)?);
+            Some(Huffman::Node { freq: 0, left, right })
+        }
+        _ => None,
+    }
+}
+
+fn cabecalho_arvore(data: &[u8]) -> Option<(Huffman, usize, &[u8])> {
+    let tree_len = ler_u32(data, 0)? as usize;
+    let bit_len = ler_u32(data, 4)? as usize;
+    let tree_data = data.get(8..8usize.checked_add(tree_len)?)?;
+    let mut pos = 0;
+    let tree = desserializar(tree_data, &mut pos, 0)?;
+    let payload = &data[8 + tree_len..];
+    if pos != tree_len || payload.len() != bit_len.div_ceil(8) {
+        return None;
+    }
+    Some((tree, bit_len, payload))
+}
+
+pub fn parece_arvore(data: &[u8]) -> bool {
+    cabecalho_arvore(data).is_some()
+}
+
+pub fn ler_arvore(data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+    let (tree, bit_len, payload) = cabecalho_arvore(data).ok_or(HuffmanError::UnknownFormat)?;
+    let mut bits = BitReader::new(payload);
+    //o descompactador antigo empurrava cada byte como char numa String
+    let mut result = String::new();
+    while bits.bits_read() < bit_len as u64 {
+        match tree.decode(&mut bits)? {
+            Some(sym) if bits.bits_read() <= bit_len as u64 => result.push(sym as u8 as char),
+            _ => break,
+        }
+    }
+    Ok(result.into_bytes())
+}
+
+//compactador.rs: [u16 n][u32 bytes do texto][n x (u32 char, u32 freq)][payload].
+//a arvore era refeita pelo construir_arvore antigo, que só compara
+//frequencias; inserir as folhas na ordem do arquivo reproduz a mesma arvore
+struct NoAntigo {
+    freq: u32,
+    tree: Huffman,
+}
+
+impl PartialEq for NoAntigo {
+    fn eq(&self, other: &Self) -> bool {
+        self.freq == other.freq
+    }
+}
+impl Eq for NoAntigo {}
+
+impl PartialOrd for NoAntigo {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NoAntigo {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.freq.cmp(&self.freq)
+    }
+}
+
+fn construir_arvore(entradas: &[(char, u32)]) -> Huffman {
+    let mut heap = BinaryHeap::new();
+    for &(c, freq) in entradas {
+        let leaf = Huffman::Leaf { sym: c as u32, freq: freq as u64 };
+        heap.push(NoAntigo { freq, tree: leaf });
+    }
+    while heap.len() > 1 {
+        let n1 = heap.pop().unwrap();
+        let n2 = heap.pop().unwrap();
+        heap.push(NoAntigo {
+            freq: n1.freq + n2.freq,
+            tree: Huffman::Node {
+                freq: (n1.freq + n2.freq) as u64,
+                left: Box::new(n1.tree),
+                right: Box::new(n2.tree),
+            },
+        });
+    }
+    heap.pop().unwrap().tree
+}
+
+fn cabecalho_tabela(data: &[u8]) -> Option<(Huffman, u64, &[u8])> {
+    let n = u16::from_be_bytes([*data.first()?, *data.get(1)?]) as usize;
+    let t = ler_u32(data, 2)? as u64;
+    if n == 0 {
+        return None;
+    }
+    let mut entradas = Vec::with_capacity(n);
+    let mut bytes_texto = 0u64;
+    for i in 0..n {
+        let c = char::from_u32(ler_u32(data, 6 + i * 8)?)?;
+        let freq = ler_u32(data, 10 + i * 8)?;
+        if freq == 0 {
+            return None;
+        }
+        bytes_texto += freq as u64 * c.len_utf8() as u64;
+        entradas.push((c, freq));
+    }
+    if bytes_texto != t {
+        return None;
+    }
+    let tree = construir_arvore(&entradas);
+    let freqs: HashMap<u32, u32> = entradas.iter().map(|&(c, f)| (c as u32, f)).collect();
+    let bits: u64 = tree
+        .code_lengths()
+        .iter()
+        .map(|&(sym, len)| freqs.get(&sym).copied().unwrap_or(0) as u64 * len as u64)
+        .sum();
+    let payload = &data[6 + n * 8..];
+    if payload.len() as u64 != bits.div_ceil(8) {
+        return None;
+    }
+    Some((tree, t, payload))
+}
+
+pub fn parece_tabela(data: &[u8]) -> bool {
+    cabecalho_tabela(data).is_some()
+}
+
+pub fn ler_tabela(data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+    let (tree, t, payload) = cabecalho_tabela(data).ok_or(HuffmanError::UnknownFormat)?;
+    let mut bits = BitReader::new(payload);
+    let mut result = String::new();
+    while (result.len() as u64) < t {
+        let sym = tree
+            .decode(&mut bits)?
+            .ok_or(HuffmanError::Corrupt("payload menor que o texto"))?;
+        result.push(char::from_u32(sym).ok_or(HuffmanError::InvalidCodePoint(sym))?);
+    }
+    Ok(result.into_bytes())
+}
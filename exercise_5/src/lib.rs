@@ -0,0 +1,145 @@
+//codec do exercicio 5: Huffman (estático, adaptativo, de ordem 1 e 2, com
+//LZ77, BWT ou sobre palavras) e rANS no formato .huff, dicionarios
+//treinados, leitura dos formatos antigos e gzip/zlib. Encoder e Decoder são a entrada normal;
+//os modulos ficam publicos para quem precisa das peças
+pub mod adaptive;
+pub mod analyze;
+pub mod archive;
+pub mod bits;
+pub mod bwt;
+pub mod context;
+pub mod container;
+pub mod crc32;
+pub mod deflate;
+pub mod dictionary;
+pub mod error;
+pub mod huffman;
+pub mod legacy;
+pub mod lz77;
+pub mod parallel;
+pub mod rans;
+pub mod rle;
+pub mod stream;
+pub mod symbol;
+pub mod tokens;
+
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::sync::Arc;
+
+pub use container::{info, Coder, Format, Info, Method, SeekIndex};
+pub use deflate::Wrapper;
+pub use dictionary::Dictionary;
+pub use error::HuffmanError;
+pub use huffman::Alphabet;
+pub use stream::Options;
+
+//compacta com as opções dadas
+#[derive(Debug, Clone, Default)]
+pub struct Encoder {
+    options: Options,
+}
+
+impl Encoder {
+    pub fn new(options: Options) -> Self {
+        Encoder { options }
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    //funciona com pipes; devolve quantos bytes foram lidos
+    pub fn compress<R: Read, W: Write>(&self, input: R, output: W) -> Result<u64, HuffmanError> {
+        stream::compress(input, output, &self.options)
+    }
+
+    //modo de duas passadas, com uma tabela só para a entrada inteira. os
+    //outros métodos, o gzip/zlib, o rANS, o dicionario, o RLE e o indice de
+    //busca (que precisa de varios blocos) não precisam de Seek e usam compress
+    pub fn compress_seekable<R: Read + Seek, W: Write>(
+        &self,
+        input: R,
+        output: W,
+    ) -> Result<u64, HuffmanError> {
+        if self.options.wrapper.is_some()
+            || self.options.method != Method::Static
+            || self.options.seek_index
+            || self.options.coder == Coder::Rans
+            || self.options.dictionary.is_some()
+            || self.options.rle
+        {
+            return self.compress(input, output);
+        }
+        stream::compress_seekable(input, output, &self.options)
+    }
+
+    pub fn compress_bytes(&self, data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+        let mut out = Vec::with_capacity(data.len() / 2);
+        self.compress(data, &mut out)?;
+        Ok(out)
+    }
+}
+
+//descompacta qualquer formato reconhecido por container::detect
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    threads: usize,
+    //os arquivos do método Dictionary pedem um destes pelo id
+    dictionaries: Vec<Arc<Dictionary>>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Decoder { threads: parallel::default_threads(), dictionaries: Vec::new() }
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Decoder::default()
+    }
+
+    //quantos blocos são decodificados ao mesmo tempo
+    pub fn with_threads(threads: usize) -> Self {
+        Decoder { threads, dictionaries: Vec::new() }
+    }
+
+    //acrescenta um dicionario aos que podem ser pedidos pelos arquivos
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionaries.push(Arc::new(dictionary));
+        self
+    }
+
+    //devolve quantos bytes foram escritos
+    pub fn decompress<R: Read, W: Write>(&self, input: R, output: W) -> Result<u64, HuffmanError> {
+        stream::decompress_with(input, output, self.threads, &self.dictionaries)
+    }
+
+    pub fn decompress_bytes(&self, data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+        let mut out = Vec::new();
+        self.decompress(data, &mut out)?;
+        Ok(out)
+    }
+
+    //de arquivo para arquivo: um .huff com indice de busca é decodificado
+    //em paralelo, cada bloco escrito direto na sua posição de `output`
+    //(stream::decompress_file). o resto passa por decompress
+    pub fn decompress_file(&self, input: &File, output: &File) -> Result<u64, HuffmanError> {
+        stream::decompress_file(input, output, self.threads, &self.dictionaries)
+    }
+
+    //acesso aleatório num .huff gravado com Options::seek_index: devolve
+    //até `len` bytes a partir de `offset` do original, decodificando só os
+    //blocos necessarios. quem faz muitas leituras no mesmo arquivo pode
+    //carregar o SeekIndex uma vez e chamar stream::read_at
+    pub fn read_at<R: Read + Seek>(
+        &self,
+        mut input: R,
+        offset: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, HuffmanError> {
+        let index = SeekIndex::load(&mut input)?;
+        stream::read_at(input, &index, offset, len, &self.dictionaries)
+    }
+}
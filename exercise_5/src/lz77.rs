@@ -0,0 +1,289 @@
+//LZ77 (variante LZSS) na frente do Huffman: cada posição vira um literal
+//ou uma referencia (comprimento, distancia) para algo que já apareceu
+//dentro da janela. as correspondencias são achadas com cadeias de hash
+//sobre 3 bytes, e o nivel decide quanto da cadeia é percorrido.
+//
+//os simbolos seguem o DEFLATE: 0..=255 são literais, 257..=285 são
+//comprimentos (com bits extras) e as distancias têm o seu proprio
+//alfabeto, com o mesmo esquema de bits extras estendido até MAX_WINDOW
+use std::io::{self, Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::HuffmanError;
+use crate::huffman::DecodeTable;
+
+pub const MIN_MATCH: usize = 3;
+pub const MAX_MATCH: usize = 258;
+pub const DEFAULT_WINDOW: usize = 32 * 1024;
+pub const MAX_WINDOW: usize = 1 << 24;
+pub const DEFAULT_LEVEL: u8 = 6;
+pub const MAX_LEVEL: u8 = 9;
+//maior simbolo de cada alfabeto
+pub const MAX_LITLEN_SYMBOL: u32 = 285;
+pub const MAX_DIST_SYMBOL: u32 = 47;
+
+//comprimento base e bits extras dos simbolos 257..=285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+const HASH_BITS: u32 = 15;
+const NENHUM: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u32 },
+}
+
+//(simbolo, bits extras, valor dos extras) de um comprimento 3..=258
+pub fn length_code(len: usize) -> (u32, u32, u32) {
+    let i = if len == MAX_MATCH {
+        LENGTH_BASE.len() - 1
+    } else {
+        LENGTH_BASE[..LENGTH_BASE.len() - 1].partition_point(|&b| b as usize <= len) - 1
+    };
+    (257 + i as u32, LENGTH_EXTRA[i] as u32, (len - LENGTH_BASE[i] as usize) as u32)
+}
+
+//(comprimento base, bits extras) de um simbolo 257..=285
+pub fn length_base(sym: u32) -> Option<(usize, u32)> {
+    let i = sym.checked_sub(257)? as usize;
+    Some((*LENGTH_BASE.get(i)? as usize, LENGTH_EXTRA[i] as u32))
+}
+
+//(simbolo, bits extras, valor dos extras) de uma distancia. as distancias
+//1..=4 têm simbolo proprio; depois cada potencia de 2 é dividida em dois
+//simbolos, o que dá exatamente a tabela do DEFLATE até 32 KiB
+pub fn distance_code(dist: usize) -> (u32, u32, u32) {
+    let d = (dist - 1) as u32;
+    if d < 4 {
+        return (d, 0, 0);
+    }
+    let msb = 31 - d.leading_zeros();
+    let extra = msb - 1;
+    (2 * msb + ((d >> extra) & 1), extra, d & ((1 << extra) - 1))
+}
+
+//(distancia base, bits extras) de um simbolo de distancia
+pub fn distance_base(sym: u32) -> Option<(usize, u32)> {
+    if sym > MAX_DIST_SYMBOL {
+        return None;
+    }
+    if sym < 4 {
+        return Some((sym as usize + 1, 0));
+    }
+    let extra = sym / 2 - 1;
+    Some((((2 | (sym & 1) as usize) << extra) + 1, extra))
+}
+
+//quanto procurar em cada nivel: (elos da cadeia, comprimento bom o
+//bastante para parar, avaliação preguiçosa), mais ou menos como o zlib
+fn parametros(level: u8) -> (usize, usize, bool) {
+    match level {
+        0 => (0, 0, false),
+        1 => (4, 8, false),
+        2 => (8, 16, false),
+        3 => (16, 32, false),
+        4 => (16, 32, true),
+        5 => (32, 64, true),
+        6 => (128, 128, true),
+        7 => (256, 258, true),
+        8 => (1024, 258, true),
+        _ => (4096, 258, true),
+    }
+}
+
+//cadeias de hash: head guarda a posição mais recente de cada hash e prev a
+//posição anterior com o mesmo hash
+struct Cadeias {
+    head: Vec<u32>,
+    prev: Vec<u32>,
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+    (v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+impl Cadeias {
+    fn new(n: usize) -> Self {
+        Cadeias { head: vec![NENHUM; 1 << HASH_BITS], prev: vec![NENHUM; n] }
+    }
+
+    fn inserir(&mut self, data: &[u8], i: usize) {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            self.prev[i] = self.head[h];
+            self.head[h] = i as u32;
+        }
+    }
+
+    //maior correspondencia para a posição i, como (comprimento, distancia)
+    fn melhor(&self, data: &[u8], i: usize, window: usize, elos: usize, bom: usize) -> (usize, usize) {
+        if i + MIN_MATCH > data.len() {
+            return (0, 0);
+        }
+        let max = (data.len() - i).min(MAX_MATCH);
+        let (mut len, mut dist) = (0, 0);
+        let mut j = self.head[hash(data, i)];
+        for _ in 0..elos {
+            if j == NENHUM || i - j as usize > window {
+                break;
+            }
+            let c = j as usize;
+            if data[c + len.min(max - 1)] == data[i + len.min(max - 1)] {
+                let n = data[c..c + max].iter().zip(&data[i..i + max]).take_while(|(a, b)| a == b).count();
+                if n > len {
+                    len = n;
+                    dist = i - c;
+                    if n >= bom || n == max {
+                        break;
+                    }
+                }
+            }
+            j = self.prev[c];
+        }
+        if len < MIN_MATCH {
+            (0, 0)
+        } else {
+            (len, dist)
+        }
+    }
+}
+
+//divide `data` em literais e referencias. as referencias nunca saem de
+//`data`, então cada bloco pode ser decodificado sozinho
+pub fn tokenize(data: &[u8], window: usize, level: u8) -> Vec<Token> {
+    tokenize_from(data, 0, window, level)
+}
+
+//como tokenize, mas só a partir de `inicio`: os bytes antes dele já foram
+//enviados e servem apenas de historico para as referencias
+pub fn tokenize_from(data: &[u8], inicio: usize, window: usize, level: u8) -> Vec<Token> {
+    let (elos, bom, preguicoso) = parametros(level);
+    let window = window.clamp(1, MAX_WINDOW);
+    let mut tokens = Vec::with_capacity((data.len() - inicio) / 2);
+    let mut cadeias = Cadeias::new(data.len());
+    for i in 0..inicio {
+        cadeias.inserir(data, i);
+    }
+    //correspondencia já calculada para a posição atual na avaliação preguiçosa
+    let mut adiantada = None;
+    let mut i = inicio;
+    while i < data.len() {
+        let (len, dist) = match adiantada.take() {
+            Some(m) => m,
+            None => cadeias.melhor(data, i, window, elos, bom),
+        };
+        cadeias.inserir(data, i);
+        if len == 0 {
+            tokens.push(Token::Literal(data[i]));
+            i += 1;
+            continue;
+        }
+        //se a proxima posição tem uma correspondencia melhor, esta vira literal
+        if preguicoso && len < bom {
+            let seguinte = cadeias.melhor(data, i + 1, window, elos, bom);
+            if seguinte.0 > len {
+                tokens.push(Token::Literal(data[i]));
+                adiantada = Some(seguinte);
+                i += 1;
+                continue;
+            }
+        }
+        tokens.push(Token::Match { len: len as u16, dist: dist as u32 });
+        for k in i + 1..i + len {
+            cadeias.inserir(data, k);
+        }
+        i += len;
+    }
+    tokens
+}
+
+//simbolo de comprimento/literal e simbolo de distancia de cada token, para
+//contar as frequencias das duas tabelas
+pub fn symbols(token: Token) -> (u32, Option<u32>) {
+    match token {
+        Token::Literal(b) => (b as u32, None),
+        Token::Match { len, dist } => {
+            (length_code(len as usize).0, Some(distance_code(dist as usize).0))
+        }
+    }
+}
+
+//escreve os tokens com os códigos das duas tabelas, indexados por simbolo
+pub fn write_tokens<W: Write>(
+    tokens: &[Token],
+    litlen: &[(u64, u8)],
+    dist: &[(u64, u8)],
+    bits: &mut BitWriter<W>,
+) -> io::Result<()> {
+    for &token in tokens {
+        match token {
+            Token::Literal(b) => {
+                let (code, n) = litlen[b as usize];
+                bits.write_bits(code, n as u32)?;
+            }
+            Token::Match { len, dist: d } => {
+                let (sym, extra, valor) = length_code(len as usize);
+                let (code, n) = litlen[sym as usize];
+                bits.write_bits(code, n as u32)?;
+                bits.write_bits(valor as u64, extra)?;
+                let (sym, extra, valor) = distance_code(d as usize);
+                let (code, n) = dist[sym as usize];
+                bits.write_bits(code, n as u32)?;
+                bits.write_bits(valor as u64, extra)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn ler_extra<R: Read>(bits: &mut BitReader<R>, n: u32) -> Result<usize, HuffmanError> {
+    let (v, disponiveis) = bits.peek(n)?;
+    if disponiveis < n {
+        return Err(HuffmanError::Corrupt("payload menor que o bloco"));
+    }
+    bits.consume(n);
+    Ok(v as usize)
+}
+
+//decodifica tokens até completar `raw_len` bytes. as referencias apontam
+//para o proprio bloco, então ele é montado inteiro antes de ir para `out`
+pub fn decode<R: Read, W: Write>(
+    raw_len: u64,
+    litlen: &DecodeTable,
+    dist: &DecodeTable,
+    bits: &mut BitReader<R>,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    let mut buf: Vec<u8> = Vec::with_capacity(raw_len.min(1 << 24) as usize);
+    let incompleto = || HuffmanError::Corrupt("payload menor que o bloco");
+    while (buf.len() as u64) < raw_len {
+        let sym = litlen.decode(bits)?.ok_or_else(incompleto)?;
+        if sym < 256 {
+            buf.push(sym as u8);
+            continue;
+        }
+        let (base, extra) = length_base(sym).ok_or(HuffmanError::Corrupt("comprimento invalido"))?;
+        let len = base + ler_extra(bits, extra)?;
+        let sym = dist.decode(bits)?.ok_or_else(incompleto)?;
+        let (base, extra) = distance_base(sym).ok_or(HuffmanError::Corrupt("distancia invalida"))?;
+        let d = base + ler_extra(bits, extra)?;
+        if d > buf.len() || (buf.len() + len) as u64 > raw_len {
+            return Err(HuffmanError::Corrupt("referencia fora do bloco"));
+        }
+        //a copia pode se sobrepor ao que está sendo escrito (d < len)
+        let inicio = buf.len() - d;
+        for k in 0..len {
+            buf.push(buf[inicio + k]);
+        }
+    }
+    out.write_all(&buf)?;
+    Ok(())
+}
@@ -0,0 +1,481 @@
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::io::{self, Read, Write};
+use std::process;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use huffman::analyze::analyze_with;
+use huffman::archive::{self, EntryKind};
+use huffman::{
+    bwt, context, info, lz77, stream, Alphabet, Coder, Decoder, Dictionary, Encoder, Format,
+    Method, Options, Wrapper,
+};
+
+const USO: &str = "uso:
+  huffman compress [opções] <entrada> <saida>
+  huffman decompress [--threads=N] [--dict=arquivo] <entrada> <saida>
+  huffman info <arquivo>
+  huffman analyze [--json] [--chars] [--dict=arquivo] <arquivo>
+  huffman test [--dict=arquivo] <arquivo>
+  huffman dict train [--chars] [--max-bits=N] <saida.dict> <amostras...>
+  huffman archive create [opções] <arquivo.hua> <caminhos...>
+  huffman archive list <arquivo.hua>
+  huffman archive extract [--bits-especiais] <arquivo.hua> <destino>
+  huffman archive extract <arquivo.hua> <membro> -
+\"-\" no lugar de um caminho usa stdin ou stdout. no archive, \"-\" como
+destino escreve só o membro pedido no stdout. setuid, setgid e sticky só
+são restaurados com --bits-especiais.
+
+opções do compress:
+  --chars          alfabeto de code points (a entrada precisa ser UTF-8)
+  --duas-passadas  uma tabela só para o arquivo todo (não funciona com pipe)
+  --adaptativo     Huffman adaptativo, sem tabela
+  --rans           rANS no lugar do Huffman estático (melhor com um simbolo
+                   muito frequente; compare com \"huffman info\")
+  --rle            sequencias de um simbolo só viram uma repetição antes do
+                   Huffman estático, nos blocos em que isso ajuda (bitmaps,
+                   registros com enchimento)
+  --lz77           LZ77 antes do Huffman
+  --bwt            BWT, move-to-front e sequencias de zeros antes do Huffman,
+                   como o bzip2 (use --bloco=N para blocos de até 16 MiB)
+  --palavras       palavras e separadores repetidos viram um simbolo só, com
+                   o vocabulario de cada bloco gravado nele (bom para texto)
+  --tokens=arquivo como --palavras, mas quebrando a entrada nos tokens do
+                   arquivo (um por linha)
+  --contexto=N     uma tabela por contexto: o byte anterior (1) ou os dois
+                   anteriores (2); contextos raros usam a tabela de ordem 0
+  --gzip, --zlib   DEFLATE legivel pelo gzip e pelo zlib do sistema
+  --nivel=N        esforço do LZ77 e do DEFLATE, de 0 a 9 (6)
+  --janela=N       janela do LZ77 em bytes (32768)
+  --max-bits=N     tamanho maximo dos códigos, de 1 a 57 (15)
+  --bloco=N        bytes de entrada por bloco, até 1 GiB (1048576)
+  --indice         grava um indice de busca com um ponto por bloco; o
+                   decompress de arquivo para arquivo usa o indice para
+                   decodificar os blocos em paralelo
+  --sem-crc        não grava o CRC de cada bloco (o do arquivo inteiro
+                   continua; o test só não consegue dizer qual bloco estragou)
+  --dict=arquivo   usa a tabela de um dicionario do \"dict train\" (bom para
+                   mensagens pequenas; o decompress precisa do mesmo --dict)
+  --threads=N      blocos codificados ao mesmo tempo";
+
+fn abrir(caminho: &str) -> io::Result<Box<dyn Read>> {
+    if caminho == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(caminho)?))
+    }
+}
+
+fn criar(caminho: &str) -> io::Result<Box<dyn Write>> {
+    if caminho == "-" {
+        Ok(Box::new(io::stdout().lock()))
+    } else {
+        Ok(Box::new(File::create(caminho)?))
+    }
+}
+
+fn ler_dicionario(caminho: &str) -> Result<Dictionary, Box<dyn Error>> {
+    Dictionary::read(io::BufReader::new(File::open(caminho)?))
+        .map_err(|e| format!("{}: {}", caminho, e).into())
+}
+
+//tokens do --tokens: as linhas não vazias do arquivo
+fn ler_tokens(caminho: &str) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    let dados = std::fs::read(caminho).map_err(|e| format!("{}: {}", caminho, e))?;
+    Ok(dados.split(|&b| b == b'\n').filter(|t| !t.is_empty()).map(<[u8]>::to_vec).collect())
+}
+
+//valor de uma opção --nome=N entre min e max
+fn numero<T: FromStr + PartialOrd + std::fmt::Display>(
+    nome: &str,
+    valor: &str,
+    min: T,
+    max: T,
+) -> Result<T, String> {
+    match valor.parse() {
+        Ok(n) if n >= min && n <= max => Ok(n),
+        _ => Err(format!("{} precisa ser um número entre {} e {}", nome, min, max)),
+    }
+}
+
+//separa as opções (--algo) dos caminhos
+fn separar(args: &[String]) -> (Vec<&str>, Vec<&str>) {
+    args.iter().map(String::as_str).partition(|a| a.starts_with("--"))
+}
+
+//opções do compress, também usadas pelo archive create. devolve se
+//--duas-passadas foi pedido
+fn opcoes(flags: &[&str]) -> Result<(Options, bool), Box<dyn Error>> {
+    let mut opcoes = Options::default();
+    let mut duas_passadas = false;
+    for &flag in flags {
+        let (nome, valor) = flag.split_once('=').unwrap_or((flag, ""));
+        match nome {
+            "--chars" => opcoes.alphabet = Alphabet::Chars,
+            "--duas-passadas" => duas_passadas = true,
+            "--adaptativo" => opcoes.method = Method::Adaptive,
+            "--lz77" => opcoes.method = Method::Lz77,
+            "--bwt" => opcoes.method = Method::Bwt,
+            "--palavras" => opcoes.method = Method::Tokens,
+            "--contexto" => {
+                opcoes.method = Method::Context;
+                opcoes.context_order = numero(nome, valor, 1, context::MAX_ORDER)?;
+            }
+            "--tokens" => {
+                opcoes.method = Method::Tokens;
+                opcoes.tokens = Some(Arc::new(ler_tokens(valor)?));
+            }
+            "--gzip" => opcoes.wrapper = Some(Wrapper::Gzip),
+            "--zlib" => opcoes.wrapper = Some(Wrapper::Zlib),
+            "--nivel" => opcoes.level = numero(nome, valor, 0, lz77::MAX_LEVEL)?,
+            "--janela" => opcoes.window = numero(nome, valor, 1, lz77::MAX_WINDOW)?,
+            "--max-bits" => opcoes.max_code_len = numero(nome, valor, 1, 57)?,
+            "--bloco" => opcoes.block_size = numero(nome, valor, 4, stream::MAX_BLOCK_SIZE)?,
+            "--threads" => opcoes.threads = numero(nome, valor, 1, 1024)?,
+            "--indice" => opcoes.seek_index = true,
+            "--sem-crc" => opcoes.block_checksums = false,
+            "--rans" => opcoes.coder = Coder::Rans,
+            "--rle" => opcoes.rle = true,
+            "--dict" => opcoes.dictionary = Some(Arc::new(ler_dicionario(valor)?)),
+            _ => return Err(format!("opção desconhecida: {}\n{}", flag, USO).into()),
+        }
+    }
+    if opcoes.method == Method::Lz77 && opcoes.alphabet == Alphabet::Chars {
+        return Err("--lz77 trabalha sobre bytes, não dá para usar com --chars".into());
+    }
+    if opcoes.method == Method::Bwt && opcoes.alphabet == Alphabet::Chars {
+        return Err("--bwt trabalha sobre bytes, não dá para usar com --chars".into());
+    }
+    if opcoes.method == Method::Tokens && opcoes.alphabet == Alphabet::Chars {
+        return Err("--palavras trabalha sobre bytes, não dá para usar com --chars".into());
+    }
+    if opcoes.method == Method::Context && opcoes.alphabet == Alphabet::Chars {
+        return Err("--contexto trabalha sobre bytes, não dá para usar com --chars".into());
+    }
+    if opcoes.method == Method::Bwt && opcoes.block_size > bwt::MAX_BLOCK {
+        return Err(format!("--bloco com --bwt vai até {} bytes", bwt::MAX_BLOCK).into());
+    }
+    let estatico = opcoes.method == Method::Static && opcoes.wrapper.is_none();
+    if opcoes.coder == Coder::Rans && (!estatico || duas_passadas) {
+        return Err("--rans só funciona no Huffman estático do modo de blocos".into());
+    }
+    if opcoes.rle && (!estatico || opcoes.coder == Coder::Rans || duas_passadas) {
+        return Err("--rle só funciona no Huffman estático do modo de blocos".into());
+    }
+    let sem_blocos = duas_passadas || opcoes.method == Method::Adaptive || opcoes.wrapper.is_some();
+    if opcoes.seek_index && sem_blocos {
+        return Err("--indice só funciona no modo de blocos (estático ou --lz77)".into());
+    }
+    if let Some(dict) = &opcoes.dictionary {
+        if !estatico || opcoes.coder == Coder::Rans || duas_passadas {
+            return Err("--dict só funciona no Huffman estático do modo de blocos".into());
+        }
+        if flags.contains(&"--chars") && dict.alphabet() != Alphabet::Chars {
+            return Err("o dicionario foi treinado com o alfabeto de bytes".into());
+        }
+    }
+    Ok((opcoes, duas_passadas))
+}
+
+//--threads=N e --dict=arquivo do decompress e do test
+fn decoder(flags: &[&str], threads: bool) -> Result<Decoder, Box<dyn Error>> {
+    let mut decoder = Decoder::new();
+    let mut dicionarios = Vec::new();
+    for &flag in flags {
+        match flag.split_once('=') {
+            Some(("--threads", valor)) if threads => {
+                decoder = Decoder::with_threads(numero("--threads", valor, 1, 1024)?)
+            }
+            Some(("--dict", valor)) => dicionarios.push(ler_dicionario(valor)?),
+            _ => return Err(format!("opção desconhecida: {}\n{}", flag, USO).into()),
+        }
+    }
+    Ok(dicionarios.into_iter().fold(decoder, Decoder::with_dictionary))
+}
+
+fn compactar(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (flags, caminhos) = separar(args);
+    let [entrada, saida] = caminhos[..] else {
+        return Err(USO.into());
+    };
+    let (opcoes, duas_passadas) = opcoes(&flags)?;
+    if duas_passadas && entrada == "-" {
+        return Err("--duas-passadas precisa de um arquivo de entrada, não de um pipe".into());
+    }
+    //a saida só é criada (e truncada) depois de a linha de comando conferir
+    let encoder = Encoder::new(opcoes);
+    if duas_passadas {
+        let entrada = File::open(entrada)?;
+        encoder.compress_seekable(entrada, criar(saida)?)?;
+    } else {
+        let entrada = abrir(entrada)?;
+        encoder.compress(entrada, criar(saida)?)?;
+    }
+    if saida != "-" {
+        println!("Arquivo compactado com sucesso.");
+    }
+    Ok(())
+}
+
+fn descompactar(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (flags, caminhos) = separar(args);
+    let [entrada, saida] = caminhos[..] else {
+        return Err(USO.into());
+    };
+    let decoder = decoder(&flags, true)?;
+    //entre arquivos os blocos de um .huff com indice vão direto para a posição
+    //deles na saida, em paralelo
+    if entrada != "-" && saida != "-" {
+        decoder.decompress_file(&File::open(entrada)?, &File::create(saida)?)?;
+    } else {
+        decoder.decompress(abrir(entrada)?, criar(saida)?)?;
+    }
+    if saida != "-" {
+        println!("Arquivo descompactado com sucesso.");
+    }
+    Ok(())
+}
+
+fn nome_do_formato(format: Format) -> &'static str {
+    match format {
+        Format::Huff => ".huff",
+        Format::LegacyTree => "arvore do antigo main.rs",
+        Format::LegacyFreqTable => "tabela do antigo compactador.rs",
+        Format::Gzip => "gzip",
+        Format::Zlib => "zlib",
+    }
+}
+
+fn mostrar_info(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [arquivo] = args else {
+        return Err(USO.into());
+    };
+    let info = info(abrir(arquivo)?)?;
+    println!("formato:     {}", nome_do_formato(info.format));
+    if let Some(header) = info.header {
+        let metodo = match header.method {
+            Method::Static if header.coder == Coder::Rans => "rANS estático",
+            Method::Static => "Huffman estático",
+            Method::Adaptive => "Huffman adaptativo",
+            Method::Lz77 => "LZ77 + Huffman",
+            Method::Bwt => "BWT + MTF + Huffman",
+            Method::Dictionary => "Huffman com dicionario",
+            Method::Tokens => "tokens + Huffman",
+            Method::Context => "Huffman por contexto",
+        };
+        let alfabeto = match header.alphabet {
+            Alphabet::Bytes => "bytes",
+            Alphabet::Chars => "chars",
+        };
+        println!("método:      {}", metodo);
+        println!("alfabeto:    {}", alfabeto);
+        println!("blocos:      {}", info.blocks);
+        println!("indice:      {}", if header.indexed { "sim" } else { "não" });
+        println!("crc/bloco:   {}", if header.block_crc { "sim" } else { "não" });
+        if header.rle {
+            println!("rle:         medido por bloco");
+        }
+        if let Some(id) = header.dictionary {
+            println!("dicionario:  {:08x}", id);
+        }
+    }
+    println!("compactado:  {} bytes", info.compressed_len);
+    if let Some(original) = info.original_len {
+        println!("original:    {} bytes", original);
+        if original > 0 {
+            println!("taxa:        {:.2}%", 100.0 * info.compressed_len as f64 / original as f64);
+        }
+    }
+    Ok(())
+}
+
+//como mostrar um simbolo na tabela do analyze
+fn simbolo(sym: u32, alphabet: Alphabet) -> String {
+    match (alphabet, char::from_u32(sym)) {
+        (Alphabet::Bytes, Some(c)) if c.is_ascii_graphic() => format!("'{}'", c),
+        (Alphabet::Bytes, _) => format!("0x{:02x}", sym),
+        (Alphabet::Chars, Some(c)) => format!("{:?}", c),
+        (Alphabet::Chars, None) => format!("U+{:04X}", sym),
+    }
+}
+
+//estatisticas de entropia e da tabela de Huffman de um arquivo ou .huff
+fn analisar(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (flags, caminhos) = separar(args);
+    let [arquivo] = caminhos[..] else {
+        return Err(USO.into());
+    };
+    let mut json = false;
+    let mut alfabeto = Alphabet::Bytes;
+    let mut decoder = Decoder::new();
+    for flag in flags {
+        match flag.split_once('=') {
+            None if flag == "--json" => json = true,
+            None if flag == "--chars" => alfabeto = Alphabet::Chars,
+            Some(("--dict", valor)) => decoder = decoder.with_dictionary(ler_dicionario(valor)?),
+            _ => return Err(format!("opção desconhecida: {}\n{}", flag, USO).into()),
+        }
+    }
+    let mut dados = Vec::new();
+    abrir(arquivo)?.read_to_end(&mut dados)?;
+    let analise = analyze_with(&dados, alfabeto, &decoder)?;
+    if json {
+        println!("{}", analise.to_json());
+        return Ok(());
+    }
+    println!("simbolos:         {}", analise.symbols);
+    println!("distintos:        {}", analise.table.len());
+    println!("entropia:         {:.4} bits/simbolo", analise.entropy);
+    println!("código medio:     {:.4} bits/simbolo", analise.average_code_len);
+    println!("original:         {} bytes", analise.original_len);
+    println!("compactado:       {} bytes", analise.compressed_len);
+    println!("cabeçalho:        {} bytes", analise.header_overhead);
+    println!("taxa:             {:.2}%", 100.0 * analise.ratio());
+    println!();
+    println!("{:>10} {:>12} {:>8}  código", "simbolo", "contagem", "%");
+    for linha in &analise.table {
+        println!(
+            "{:>10} {:>12} {:>7.3}%  {}",
+            simbolo(linha.symbol, analise.alphabet),
+            linha.count,
+            100.0 * linha.count as f64 / analise.symbols as f64,
+            linha.code
+        );
+    }
+    Ok(())
+}
+
+//descompacta sem gravar nada, só para conferir tamanho e CRC. com CRC por
+//bloco o erro diz qual bloco está estragado
+fn testar(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (flags, caminhos) = separar(args);
+    let [arquivo] = caminhos[..] else {
+        return Err(USO.into());
+    };
+    let total = decoder(&flags, false)?
+        .decompress(abrir(arquivo)?, io::sink())
+        .map_err(|e| format!("{}: {}", arquivo, e))?;
+    println!("{}: ok ({} bytes)", arquivo, total);
+    Ok(())
+}
+
+//diretorio (canonico) onde um arquivo vai ser criado
+fn pasta_de(arquivo: &str) -> Option<PathBuf> {
+    match Path::new(arquivo).parent() {
+        Some(pasta) if !pasta.as_os_str().is_empty() => pasta.canonicalize().ok(),
+        _ => env::current_dir().ok(),
+    }
+}
+
+fn arquivar(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let Some((acao, resto)) = args.split_first() else {
+        return Err(USO.into());
+    };
+    let (flags, caminhos) = separar(resto);
+    match (acao.as_str(), &caminhos[..]) {
+        ("create", [arquivo, entradas @ ..]) if !entradas.is_empty() => {
+            let (opcoes, duas_passadas) = opcoes(&flags)?;
+            if duas_passadas {
+                return Err("--duas-passadas não é usado no archive".into());
+            }
+            if opcoes.dictionary.is_some() {
+                return Err("--dict não é usado no archive".into());
+            }
+            //dentro de um dos diretorios o arquivo entraria nele mesmo
+            if let Some(pasta) = pasta_de(arquivo) {
+                for &entrada in entradas {
+                    if Path::new(entrada).canonicalize().is_ok_and(|e| pasta.starts_with(e)) {
+                        let msg = format!("{} fica dentro de {}: grave-o fora", arquivo, entrada);
+                        return Err(msg.into());
+                    }
+                }
+            }
+            let entradas = archive::create(File::create(arquivo)?, entradas, &opcoes)?;
+            println!("{} entradas arquivadas em {}", entradas.len(), arquivo);
+        }
+        ("list", [arquivo]) if flags.is_empty() => {
+            for entry in archive::list(File::open(arquivo)?)? {
+                match entry.kind {
+                    EntryKind::Dir => println!("{:04o} {:>12} {:>12}  {}/", entry.mode, "-", "-", entry.path),
+                    EntryKind::File => println!(
+                        "{:04o} {:>12} {:>12}  {}",
+                        entry.mode, entry.size, entry.compressed_size, entry.path
+                    ),
+                }
+            }
+        }
+        ("extract", [arquivo, membro, "-"]) if flags.is_empty() => {
+            archive::extract(File::open(arquivo)?, membro, io::stdout().lock())?;
+        }
+        ("extract", [arquivo, destino]) if flags.iter().all(|&f| f == "--bits-especiais") => {
+            let bits_especiais = !flags.is_empty();
+            let entrada = File::open(arquivo)?;
+            let entradas = archive::extract_all_with(entrada, Path::new(destino), bits_especiais)?;
+            println!("{} entradas extraidas em {}", entradas.len(), destino);
+        }
+        _ => return Err(USO.into()),
+    }
+    Ok(())
+}
+
+//treina um dicionario com as amostras e grava no arquivo
+fn dicionario(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let Some((acao, resto)) = args.split_first() else {
+        return Err(USO.into());
+    };
+    let (flags, caminhos) = separar(resto);
+    let ("train", [saida, amostras @ ..]) = (acao.as_str(), &caminhos[..]) else {
+        return Err(USO.into());
+    };
+    if amostras.is_empty() {
+        return Err(USO.into());
+    }
+    let mut alfabeto = Alphabet::Bytes;
+    let mut max_bits = huffman::huffman::DEFAULT_MAX_CODE_LEN;
+    for &flag in &flags {
+        match flag.split_once('=').unwrap_or((flag, "")) {
+            ("--chars", _) => alfabeto = Alphabet::Chars,
+            ("--max-bits", valor) => max_bits = numero("--max-bits", valor, 1, 57)?,
+            _ => return Err(format!("opção desconhecida: {}\n{}", flag, USO).into()),
+        }
+    }
+    let mut dados = Vec::with_capacity(amostras.len());
+    for amostra in amostras {
+        let mut buf = Vec::new();
+        abrir(amostra)?.read_to_end(&mut buf)?;
+        dados.push(buf);
+    }
+    let dict = Dictionary::train(&dados, alfabeto, max_bits)?;
+    let mut saida_arquivo = criar(saida)?;
+    dict.write(&mut saida_arquivo)?;
+    saida_arquivo.flush()?;
+    if *saida != "-" {
+        println!("dicionario {:08x} com {} simbolos", dict.id(), dict.lengths().len());
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((comando, resto)) = args.split_first() else {
+        eprintln!("{}", USO);
+        process::exit(2);
+    };
+    let resultado = match comando.as_str() {
+        "compress" | "compactar" => compactar(resto),
+        "decompress" | "descompactar" => descompactar(resto),
+        "info" => mostrar_info(resto),
+        "analyze" | "analisar" => analisar(resto),
+        "test" | "testar" => testar(resto),
+        "archive" | "arquivar" => arquivar(resto),
+        "dict" | "dicionario" => dicionario(resto),
+        _ => Err(USO.into()),
+    };
+    if let Err(e) = resultado {
+        eprintln!("Erro: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,28 @@
+//divide o trabalho entre threads do mesmo jeito que o parallel_primes do
+//exersice_6: uma thread por pedaço e join na ordem em que foram criadas,
+//então os resultados saem na ordem original
+use std::thread;
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+pub fn map_in_threads<T, R>(tarefas: Vec<T>, f: fn(T) -> R) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+{
+    if tarefas.len() <= 1 {
+        return tarefas.into_iter().map(f).collect();
+    }
+    let mut handles = vec![];
+    for tarefa in tarefas {
+        handles.push(thread::spawn(move || f(tarefa)));
+    }
+
+    let mut results = vec![];
+    for handle in handles {
+        results.push(handle.join().unwrap());
+    }
+    results
+}
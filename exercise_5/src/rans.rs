@@ -0,0 +1,196 @@
+//rANS (range asymmetric numeral systems) estático, alternativa ao Huffman
+//nos blocos do método estático. usa as mesmas frequencias de
+//contar_frequencias, mas gasta por simbolo quase exatamente -log2(p) bits em
+//vez de um número inteiro de bits, o que faz diferença quando um simbolo
+//domina a entrada (um simbolo com 90% gasta ~0.15 bit em vez de 1).
+//
+//as frequencias são normalizadas para somar 2^SCALE_BITS. o estado tem 64
+//bits e é renormalizado de 32 em 32. o compactador processa os simbolos de
+//trás para frente e o descompactador lê na ordem normal, então o payload é
+//  u64     estado final do compactador, big-endian
+//  u32...  palavras de renormalização, big-endian, na ordem de leitura
+//e termina com o estado de volta ao valor inicial, o que serve de
+//conferencia. com um único simbolo distinto o payload fica vazio, como no
+//Huffman
+use std::collections::HashMap;
+use std::io::Read;
+
+use crate::error::HuffmanError;
+use crate::huffman::{read_varint, write_varint, MAX_SYMBOLS};
+
+pub const SCALE_BITS: u32 = 24;
+const TOTAL: u64 = 1 << SCALE_BITS;
+//limite inferior do estado
+const RANS_L: u64 = 1 << 31;
+
+//frequencias que somam TOTAL, cada simbolo presente com pelo menos 1, em
+//ordem de simbolo
+pub fn normalize(freqs: &HashMap<u32, u64>) -> Vec<(u32, u32)> {
+    let soma: u64 = freqs.values().sum();
+    let mut tabela: Vec<(u32, u32)> = freqs
+        .iter()
+        .filter(|&(_, &f)| f > 0)
+        .map(|(&s, &f)| (s, ((f as u128 * TOTAL as u128 / soma as u128) as u32).max(1)))
+        .collect();
+    tabela.sort_unstable();
+    let mut total: u64 = tabela.iter().map(|&(_, f)| f as u64).sum();
+    //o arredondamento deixa a soma um pouco longe de TOTAL: a diferença vai
+    //para os simbolos mais frequentes, onde pesa menos
+    let mut ordem: Vec<usize> = (0..tabela.len()).collect();
+    ordem.sort_by_key(|&i| std::cmp::Reverse(tabela[i].1));
+    let mut i = 0;
+    while total != TOTAL && !ordem.is_empty() {
+        let f = &mut tabela[ordem[i % ordem.len()]].1;
+        if total < TOTAL {
+            let falta = (TOTAL - total) as u32;
+            *f += falta;
+            total += falta as u64;
+        } else if *f > 1 {
+            let sobra = (total - TOTAL).min(*f as u64 - 1);
+            *f -= sobra as u32;
+            total -= sobra;
+        }
+        i += 1;
+    }
+    tabela
+}
+
+pub fn write_frequencies(tabela: &[(u32, u32)], out: &mut Vec<u8>) {
+    write_varint(tabela.len() as u64, out);
+    let mut anterior = 0u32;
+    for (i, &(sym, freq)) in tabela.iter().enumerate() {
+        let delta = if i == 0 { sym } else { sym - anterior - 1 };
+        write_varint(delta as u64, out);
+        write_varint(freq as u64, out);
+        anterior = sym;
+    }
+}
+
+//lê e valida uma tabela gravada por write_frequencies
+pub fn read_frequencies<R: Read>(input: &mut R) -> Result<Vec<(u32, u32)>, HuffmanError> {
+    let n = read_varint(input)?;
+    if n == 0 || n > MAX_SYMBOLS {
+        return Err(HuffmanError::MalformedTree("tabela de frequencias com tamanho invalido"));
+    }
+    let mut tabela = Vec::with_capacity(n as usize);
+    let mut anterior = 0u64;
+    let mut total = 0u64;
+    for i in 0..n {
+        let delta = read_varint(input)?;
+        let sym = if i == 0 {
+            Some(delta)
+        } else {
+            anterior.checked_add(delta).and_then(|s| s.checked_add(1))
+        };
+        let sym = match sym {
+            Some(sym) if sym <= u32::MAX as u64 => sym,
+            _ => return Err(HuffmanError::MalformedTree("simbolo fora do alfabeto")),
+        };
+        let freq = read_varint(input)?;
+        if freq == 0 || freq > TOTAL {
+            return Err(HuffmanError::MalformedTree("frequencia fora do intervalo"));
+        }
+        total += freq;
+        tabela.push((sym as u32, freq as u32));
+        anterior = sym;
+    }
+    if total != TOTAL {
+        return Err(HuffmanError::MalformedTree("frequencias não somam 2^24"));
+    }
+    Ok(tabela)
+}
+
+//inicio acumulado de cada simbolo da tabela
+fn acumulados(tabela: &[(u32, u32)]) -> Vec<u32> {
+    let mut acc = 0;
+    tabela
+        .iter()
+        .map(|&(_, f)| {
+            let inicio = acc;
+            acc += f;
+            inicio
+        })
+        .collect()
+}
+
+//codifica `simbolos` com a tabela normalizada; todos precisam estar nela
+pub fn encode(simbolos: &[u32], tabela: &[(u32, u32)]) -> Vec<u8> {
+    if tabela.len() <= 1 {
+        return Vec::new();
+    }
+    let inicios = acumulados(tabela);
+    let posicao: HashMap<u32, usize> =
+        tabela.iter().enumerate().map(|(i, &(s, _))| (s, i)).collect();
+    let mut palavras = Vec::with_capacity(simbolos.len() / 8);
+    let mut x = RANS_L;
+    for s in simbolos.iter().rev() {
+        let i = posicao[s];
+        let (freq, inicio) = (tabela[i].1 as u64, inicios[i] as u64);
+        let x_max = ((RANS_L >> SCALE_BITS) << 32) * freq;
+        if x >= x_max {
+            palavras.push(x as u32);
+            x >>= 32;
+        }
+        x = ((x / freq) << SCALE_BITS) + (x % freq) + inicio;
+    }
+    let mut out = Vec::with_capacity(8 + palavras.len() * 4);
+    out.extend_from_slice(&x.to_be_bytes());
+    for p in palavras.iter().rev() {
+        out.extend_from_slice(&p.to_be_bytes());
+    }
+    out
+}
+
+//lê simbolos de um payload gravado por encode
+pub struct RansDecoder<'a> {
+    tabela: &'a [(u32, u32)],
+    inicios: Vec<u32>,
+    payload: &'a [u8],
+    x: u64,
+}
+
+impl<'a> RansDecoder<'a> {
+    pub fn new(tabela: &'a [(u32, u32)], payload: &'a [u8]) -> Result<Self, HuffmanError> {
+        let mut x = RANS_L;
+        let mut payload = payload;
+        if tabela.len() > 1 {
+            if payload.len() < 8 || !payload.len().is_multiple_of(4) {
+                return Err(HuffmanError::Corrupt("payload rANS com tamanho invalido"));
+            }
+            x = u64::from_be_bytes(payload[..8].try_into().unwrap());
+            payload = &payload[8..];
+            if x < RANS_L {
+                return Err(HuffmanError::Corrupt("estado rANS invalido"));
+            }
+        } else if !payload.is_empty() {
+            return Err(HuffmanError::Corrupt("payload rANS com tamanho invalido"));
+        }
+        Ok(RansDecoder { tabela, inicios: acumulados(tabela), payload, x })
+    }
+
+    pub fn next_symbol(&mut self) -> Result<u32, HuffmanError> {
+        if self.tabela.len() == 1 {
+            return Ok(self.tabela[0].0);
+        }
+        let fatia = (self.x & (TOTAL - 1)) as u32;
+        let i = self.inicios.partition_point(|&inicio| inicio <= fatia) - 1;
+        let (sym, freq) = self.tabela[i];
+        self.x = freq as u64 * (self.x >> SCALE_BITS) + (fatia - self.inicios[i]) as u64;
+        if self.x < RANS_L {
+            let Some((palavra, resto)) = self.payload.split_first_chunk::<4>() else {
+                return Err(HuffmanError::Corrupt("payload menor que o bloco"));
+            };
+            self.x = (self.x << 32) | u32::from_be_bytes(*palavra) as u64;
+            self.payload = resto;
+        }
+        Ok(sym)
+    }
+
+    //o payload tem que acabar junto com os simbolos, com o estado inicial
+    pub fn finish(self) -> Result<(), HuffmanError> {
+        if !self.payload.is_empty() || self.x != RANS_L {
+            return Err(HuffmanError::Corrupt("tamanho do payload não confere"));
+        }
+        Ok(())
+    }
+}
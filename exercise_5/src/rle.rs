@@ -0,0 +1,102 @@
+//RLE antes do Huffman estático: bitmaps, registros com enchimento e linhas
+//de separador têm sequencias longas de um simbolo só, e o Huffman não
+//desce de 1 bit por simbolo. uma sequencia de n >= MIN_RUN simbolos x vira
+//x seguido do simbolo RUN, cujo código vem seguido do número de repetições
+//que faltam (n - 1) em Elias gamma, cru no payload, como o simbolo depois do
+//ESCAPE do dicionario. RUN entra na tabela do bloco como qualquer simbolo.
+//
+//o compactador codifica o bloco com e sem o RLE e fica com o menor; o byte
+//de RLE no bloco (container.rs) diz qual foi
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::HuffmanError;
+use crate::huffman::MAX_SYMBOLS;
+
+//simbolo extra, fora de qualquer alfabeto
+pub const RUN: u32 = MAX_SYMBOLS as u32;
+//sequencias mais curtas ficam como estão
+pub const MIN_RUN: usize = 4;
+
+//simbolo solto ou repetições do anterior
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Piece {
+    Symbol(u32),
+    Repeat(u64),
+}
+
+pub fn runs(simbolos: &[u32]) -> Vec<Piece> {
+    let mut out = Vec::with_capacity(simbolos.len());
+    let mut i = 0;
+    while i < simbolos.len() {
+        let s = simbolos[i];
+        let n = simbolos[i..].iter().take_while(|&&x| x == s).count();
+        if n >= MIN_RUN {
+            out.push(Piece::Symbol(s));
+            out.push(Piece::Repeat(n as u64 - 1));
+        } else {
+            out.extend(std::iter::repeat_n(Piece::Symbol(s), n));
+        }
+        i += n;
+    }
+    out
+}
+
+//frequencias dos simbolos da tabela, com RUN no lugar das repetições
+pub fn frequencies(pecas: &[Piece]) -> HashMap<u32, u64> {
+    let mut freqs = HashMap::new();
+    for p in pecas {
+        let s = match *p {
+            Piece::Symbol(s) => s,
+            Piece::Repeat(_) => RUN,
+        };
+        *freqs.entry(s).or_insert(0) += 1;
+    }
+    freqs
+}
+
+pub fn write_pieces<W: Write>(
+    pecas: &[Piece],
+    codes: &HashMap<u32, (u64, u8)>,
+    bits: &mut BitWriter<W>,
+) -> io::Result<()> {
+    for p in pecas {
+        match *p {
+            Piece::Symbol(s) => {
+                let (code, len) = codes[&s];
+                bits.write_bits(code, len as u32)?;
+            }
+            Piece::Repeat(n) => {
+                let (code, len) = codes[&RUN];
+                bits.write_bits(code, len as u32)?;
+                //n >= MIN_RUN - 1, então o valor gravado é >= 1
+                let v = n - (MIN_RUN as u64 - 2);
+                let tam = 64 - v.leading_zeros();
+                bits.write_bits(0, tam - 1)?;
+                bits.write_bits(v, tam)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+//lê as repetições depois de um RUN; None se os dados acabarem antes
+pub fn read_repeat<R: Read>(bits: &mut BitReader<R>) -> Result<Option<u64>, HuffmanError> {
+    let mut zeros = 0;
+    loop {
+        match bits.read_bit()? {
+            Some(true) => break,
+            Some(false) if zeros < 56 => zeros += 1,
+            Some(false) => return Err(HuffmanError::Corrupt("repetição grande demais")),
+            None => return Ok(None),
+        }
+    }
+    let (resto, disponiveis) = bits.peek(zeros)?;
+    if disponiveis < zeros {
+        return Ok(None);
+    }
+    bits.consume(zeros);
+    let v = (1u64 << zeros) | resto;
+    Ok(Some(v + (MIN_RUN as u64 - 2)))
+}
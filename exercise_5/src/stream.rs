@@ -0,0 +1,786 @@
+//compactação e descompactação sobre qualquer Read/Write, com memoria
+//limitada independente do tamanho da entrada
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+use crate::adaptive::AdaptiveModel;
+use crate::bits::BitWriter;
+use crate::container::{self, BlockHeader, Coder, Header, Method, SeekIndex, MAGIC};
+use crate::bwt;
+use crate::context;
+use crate::crc32::{self, crc32, Crc32, CrcWriter};
+use crate::deflate::{self, Wrapper};
+use crate::dictionary::Dictionary;
+use crate::error::HuffmanError;
+use crate::huffman::{Alphabet, DEFAULT_MAX_CODE_LEN};
+use crate::legacy;
+use crate::lz77::{DEFAULT_LEVEL, DEFAULT_WINDOW};
+use crate::parallel::{default_threads, map_in_threads};
+
+//tamanho dos pedaços lidos em cada passada do modo de duas passadas
+const CHUNK: usize = 1 << 16;
+pub const DEFAULT_BLOCK_SIZE: usize = 1 << 20;
+//cada bloco fica inteiro em memoria, na compactação e na descompactação
+pub const MAX_BLOCK_SIZE: usize = 1 << 30;
+//blocos maiores que isso (o bloco único do modo de duas passadas, por
+//exemplo) são decodificados em sequencia, sem carregar tudo em memoria
+const MAX_PARALLEL_BLOCK: u64 = 64 << 20;
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub alphabet: Alphabet,
+    pub method: Method,
+    //bytes de entrada por bloco no modo de blocos, no maximo MAX_BLOCK_SIZE
+    //(na BWT, bwt::MAX_BLOCK)
+    pub block_size: usize,
+    //quantos blocos são codificados ao mesmo tempo
+    pub threads: usize,
+    //nenhum código passa desse tamanho (package-merge quando precisa)
+    pub max_code_len: u8,
+    //só no LZ77: até onde as referencias voltam e quanto esforço gastar
+    //procurando correspondencias (0 a 9)
+    pub window: usize,
+    pub level: u8,
+    //Some grava DEFLATE nesse envelope em vez do .huff (o nivel vale aqui
+    //também, o resto é ignorado)
+    pub wrapper: Option<Wrapper>,
+    //grava o indice de busca (container::SeekIndex), com um ponto por
+    //bloco. só no modo de blocos; o adaptativo e o gzip/zlib ignoram
+    pub seek_index: bool,
+    //codificador dos blocos do método estático no modo de blocos. o modo
+    //de duas passadas usa sempre Huffman
+    pub coder: Coder,
+    //Some compacta com a tabela do dicionario (Method::Dictionary) no modo
+    //de blocos; o alfabeto passa a ser o dele
+    pub dictionary: Option<Arc<Dictionary>>,
+    //grava o CRC de cada bloco depois do payload, para o descompactador
+    //dizer qual bloco está estragado. só no modo de blocos
+    pub block_checksums: bool,
+    //só no método de tokens: lista de tokens para quebrar a entrada. None
+    //usa palavras e separadores
+    pub tokens: Option<Arc<Vec<Vec<u8>>>>,
+    //só no método de contexto: 1 (byte anterior) ou 2 (hash dos dois
+    //anteriores)
+    pub context_order: u8,
+    //tenta o RLE (rle.rs) em cada bloco do Huffman estático e grava o que
+    //ficar menor. só no modo de blocos
+    pub rle: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            alphabet: Alphabet::Bytes,
+            method: Method::Static,
+            block_size: DEFAULT_BLOCK_SIZE,
+            threads: default_threads(),
+            max_code_len: DEFAULT_MAX_CODE_LEN,
+            window: DEFAULT_WINDOW,
+            level: DEFAULT_LEVEL,
+            wrapper: None,
+            seek_index: false,
+            coder: Coder::Huffman,
+            dictionary: None,
+            block_checksums: true,
+            tokens: None,
+            context_order: 1,
+            rle: false,
+        }
+    }
+}
+
+//inicio do ultimo char que pode estar incompleto no fim de `buf`. os bytes a
+//partir dali ficam para a proxima leitura; sequencias invalidas não são
+//tratadas aqui, elas falham depois em Alphabet::symbols
+fn fronteira_utf8(buf: &[u8]) -> usize {
+    for volta in 1..=buf.len().min(4) {
+        let b = buf[buf.len() - volta];
+        if b & 0xC0 != 0x80 {
+            let tam = match b {
+                0x00..=0x7F => 1,
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            return if tam > volta { buf.len() - volta } else { buf.len() };
+        }
+    }
+    buf.len()
+}
+
+//lê o proximo pedaço de até `limite` bytes. `buf` começa com o que sobrou da
+//leitura anterior; devolve quantos bytes do inicio de `buf` formam simbolos
+//completos (o resto deve ser guardado) e se a entrada acabou
+fn ler_pedaco<R: Read>(
+    input: &mut R,
+    buf: &mut Vec<u8>,
+    limite: usize,
+    alphabet: Alphabet,
+) -> io::Result<(usize, bool)> {
+    let falta = limite.saturating_sub(buf.len()) as u64;
+    let lidos = input.by_ref().take(falta).read_to_end(buf)?;
+    let fim = (lidos as u64) < falta;
+    let corte = match alphabet {
+        Alphabet::Chars if !fim => fronteira_utf8(buf),
+        _ => buf.len(),
+    };
+    Ok((corte, fim))
+}
+
+//percorre a entrada em pedaços, entregando bytes e simbolos de cada um
+fn percorrer<R: Read>(
+    input: &mut R,
+    alphabet: Alphabet,
+    mut f: impl FnMut(&[u8], &[u32]) -> Result<(), HuffmanError>,
+) -> Result<(), HuffmanError> {
+    let mut buf = Vec::with_capacity(CHUNK);
+    loop {
+        let (corte, fim) = ler_pedaco(input, &mut buf, CHUNK, alphabet)?;
+        f(&buf[..corte], &alphabet.symbols(&buf[..corte])?)?;
+        if fim {
+            return Ok(());
+        }
+        buf.drain(..corte);
+    }
+}
+
+//modo de duas passadas para entradas com Seek: a primeira conta as
+//frequencias e a segunda codifica direto na saida. grava um único bloco e o
+//tamanho original fica no cabeçalho. devolve quantos bytes foram lidos
+pub fn compress_seekable<R: Read + Seek, W: Write>(
+    input: R,
+    output: W,
+    options: &Options,
+) -> Result<u64, HuffmanError> {
+    let mut input = BufReader::new(input);
+    let mut output = BufWriter::new(output);
+    let alphabet = options.alphabet;
+    let inicio = input.stream_position()?;
+
+    let mut freqs: HashMap<u32, u64> = HashMap::new();
+    let mut crc = Crc32::new();
+    let mut total = 0u64;
+    percorrer(&mut input, alphabet, |bytes, simbolos| {
+        crc.update(bytes);
+        total += bytes.len() as u64;
+        for &s in simbolos {
+            *freqs.entry(s).or_insert(0) += 1;
+        }
+        Ok(())
+    })?;
+    input.seek(SeekFrom::Start(inicio))?;
+
+    let header = Header {
+        alphabet,
+        method: Method::Static,
+        original_len: Some(total),
+        indexed: false,
+        coder: Coder::Huffman,
+        dictionary: None,
+        block_crc: false,
+        rle: false,
+    };
+    container::write_header(&mut output, &header)?;
+    if total > 0 {
+        let codes = container::write_block_header(&mut output, &freqs, total, options.max_code_len)?;
+        let mut bits = BitWriter::new(&mut output);
+        percorrer(&mut input, alphabet, |_, simbolos| {
+            for s in simbolos {
+                let (code, len) = codes.get(s).copied().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "a entrada mudou entre as passadas")
+                })?;
+                bits.write_bits(code, len as u32)?;
+            }
+            Ok(())
+        })?;
+        bits.finish()?;
+    }
+    container::write_end(&mut output, crc.finish())?;
+    output.flush()?;
+    Ok(total)
+}
+
+//modo de blocos: cada `block_size` bytes viram um bloco com a sua propria
+//tabela. lê `threads` blocos por vez e codifica cada um numa thread, então
+//a memoria fica limitada a threads * block_size e funciona com pipes.
+//também é o modo do LZ77, da BWT e dos tokens, que transformam cada bloco
+//antes, o dos contextos e o dos dicionarios
+pub fn compress_stream<R: Read, W: Write>(
+    input: R,
+    output: W,
+    options: &Options,
+) -> Result<u64, HuffmanError> {
+    let mut input = BufReader::new(input);
+    let mut output = BufWriter::new(output);
+    //o dicionario vai junto com o método, para cada bloco receber o mesmo
+    let (method, dicionario) = match (options.method, &options.dictionary) {
+        (_, Some(dict)) => (Method::Dictionary, Some(dict.clone())),
+        (Method::Lz77 | Method::Bwt | Method::Tokens | Method::Context, _) => {
+            (options.method, None)
+        }
+        _ => (Method::Static, None),
+    };
+    //o LZ77, a BWT, os tokens e os contextos trabalham sempre sobre bytes, e o
+    //dicionario no alfabeto com que foi treinado
+    let alphabet = match (method, &dicionario) {
+        (Method::Static, _) => options.alphabet,
+        (_, Some(dict)) => dict.alphabet(),
+        _ => Alphabet::Bytes,
+    };
+    let block_size = match method {
+        Method::Bwt => options.block_size.clamp(4, bwt::MAX_BLOCK),
+        _ => options.block_size.clamp(4, MAX_BLOCK_SIZE),
+    };
+    let threads = options.threads.max(1);
+
+    let coder = if method == Method::Static { options.coder } else { Coder::Huffman };
+    let rle = options.rle && method == Method::Static && coder == Coder::Huffman;
+    let header = Header {
+        alphabet,
+        method,
+        original_len: None,
+        indexed: options.seek_index,
+        coder,
+        dictionary: dicionario.as_ref().map(|dict| dict.id()),
+        block_crc: options.block_checksums,
+        rle,
+    };
+    container::write_header(&mut output, &header)?;
+    let mut crc = Crc32::new();
+    let mut total = 0u64;
+    //posição no arquivo compactado e no original do inicio de cada bloco
+    let mut posicao = header.encoded_len() as u64;
+    let mut pontos = Vec::new();
+    let mut buf = Vec::with_capacity(block_size);
+    let mut fim = false;
+    while !fim {
+        let mut lote = Vec::with_capacity(threads);
+        while lote.len() < threads && !fim {
+            let (corte, acabou) = ler_pedaco(&mut input, &mut buf, block_size, alphabet)?;
+            fim = acabou;
+            if corte > 0 {
+                let resto = buf.split_off(corte);
+                let bloco = std::mem::replace(&mut buf, resto);
+                crc.update(&bloco);
+                pontos.push((0, total));
+                total += bloco.len() as u64;
+                let options = Options { alphabet, method, coder, rle, ..options.clone() };
+                lote.push((bloco, options, dicionario.clone()));
+            }
+        }
+        let primeiro = pontos.len() - lote.len();
+        let codificados = map_in_threads(lote, |(bloco, options, dicionario)| {
+            let mut out = match (options.method, dicionario) {
+                (_, Some(dict)) => container::encode_dict_block(&bloco, &dict),
+                (Method::Lz77, None) => container::encode_lz77_block(
+                    &bloco,
+                    options.window,
+                    options.level,
+                    options.max_code_len,
+                ),
+                (Method::Bwt, None) => container::encode_bwt_block(&bloco, options.max_code_len),
+                (Method::Tokens, None) => container::encode_tokens_block(
+                    &bloco,
+                    options.tokens.as_ref().map(|t| t.as_slice()),
+                    options.max_code_len,
+                ),
+                (Method::Context, None) => container::encode_context_block(
+                    &bloco,
+                    options.context_order.clamp(1, context::MAX_ORDER),
+                    options.max_code_len,
+                ),
+                _ if options.coder == Coder::Rans => {
+                    container::encode_rans_block(&bloco, options.alphabet)
+                }
+                _ => container::encode_block(
+                    &bloco,
+                    options.alphabet,
+                    options.max_code_len,
+                    options.rle,
+                ),
+            }?;
+            if options.block_checksums {
+                out.extend_from_slice(&crc32(&bloco).to_be_bytes());
+            }
+            Ok::<_, HuffmanError>(out)
+        });
+        for (ponto, bloco) in pontos[primeiro..].iter_mut().zip(codificados) {
+            let bloco = bloco?;
+            ponto.0 = posicao;
+            posicao += bloco.len() as u64;
+            output.write_all(&bloco)?;
+        }
+    }
+    container::write_end(&mut output, crc.finish())?;
+    if options.seek_index {
+        pontos.push((posicao, total));
+        container::write_index(&mut output, &pontos, posicao + 5)?;
+    }
+    output.flush()?;
+    Ok(total)
+}
+
+//Huffman adaptativo: não precisa conhecer a entrada antes, então cada
+//leitura vira um bloco e é escrita (e descarregada) na hora. serve para
+//compactar a saida de um processo que ainda está rodando
+pub fn compress_adaptive<R: Read, W: Write>(
+    mut input: R,
+    mut output: W,
+    options: &Options,
+) -> Result<u64, HuffmanError> {
+    let alphabet = options.alphabet;
+    let header = Header {
+        alphabet,
+        method: Method::Adaptive,
+        original_len: None,
+        indexed: false,
+        coder: Coder::Huffman,
+        dictionary: None,
+        block_crc: false,
+        rle: false,
+    };
+    container::write_header(&mut output, &header)?;
+    output.flush()?;
+
+    let mut model = AdaptiveModel::new(alphabet);
+    let mut crc = Crc32::new();
+    let mut total = 0u64;
+    let mut buf = Vec::with_capacity(CHUNK);
+    let mut leitura = vec![0u8; CHUNK];
+    loop {
+        let n = match input.read(&mut leitura) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        buf.extend_from_slice(&leitura[..n]);
+        let fim = n == 0;
+        let corte = match alphabet {
+            Alphabet::Chars if !fim => fronteira_utf8(&buf),
+            _ => buf.len(),
+        };
+        if corte > 0 {
+            let mut bits = BitWriter::new(Vec::with_capacity(corte));
+            for sym in alphabet.symbols(&buf[..corte])? {
+                model.encode(sym, &mut bits)?;
+            }
+            container::write_adaptive_block(&mut output, corte as u64, &bits.finish()?)?;
+            output.flush()?;
+            crc.update(&buf[..corte]);
+            total += corte as u64;
+            buf.drain(..corte);
+        }
+        if fim {
+            break;
+        }
+    }
+    container::write_end(&mut output, crc.finish())?;
+    output.flush()?;
+    Ok(total)
+}
+
+//escolhe o modo pelo método das opções: blocos em paralelo (estáticos,
+//LZ77, BWT, tokens ou com dicionario), adaptativo, ou gzip/zlib quando há um
+//envelope
+pub fn compress<R: Read, W: Write>(
+    input: R,
+    output: W,
+    options: &Options,
+) -> Result<u64, HuffmanError> {
+    if let Some(wrapper) = options.wrapper {
+        return deflate::compress(input, output, wrapper, options.level);
+    }
+    match options.method {
+        Method::Adaptive if options.dictionary.is_none() => {
+            compress_adaptive(input, output, options)
+        }
+        _ => compress_stream(input, output, options),
+    }
+}
+
+//erro do bloco `numero`, que começa no byte `inicio` do original. erros de
+//E/S de verdade (não do conteudo do arquivo) passam sem mudar
+fn no_bloco(numero: u64, inicio: u64) -> impl Fn(HuffmanError) -> HuffmanError + Copy {
+    move |e| match e {
+        HuffmanError::Io(_) | HuffmanError::BadBlock { .. } => e,
+        e => HuffmanError::BadBlock { block: numero, offset: inicio, error: Box::new(e) },
+    }
+}
+
+//bloco lido esperando uma thread: payload (e CRC), cabeçalho do arquivo e o
+//número do bloco com o inicio dele no original, para o erro dizer onde foi
+type Tarefa = (BlockHeader, Vec<u8>, Header, (u64, u64));
+
+//decodifica um lote de blocos já lidos, um por thread, e escreve na ordem
+//(com o CRC do bloco, se houver)
+fn decodificar_lote<W: Write>(
+    lote: &mut Vec<Tarefa>,
+    output: &mut W,
+) -> Result<(), HuffmanError> {
+    let tarefas = std::mem::take(lote);
+    for result in map_in_threads(tarefas, |(block, payload, header, (numero, inicio))| {
+        let mut out = Vec::with_capacity(block.raw_len as usize);
+        container::decode_block_checked(&block, payload.as_slice(), &header, &mut out)
+            .map(|_| out)
+            .map_err(no_bloco(numero, inicio))
+    }) {
+        output.write_all(&result?)?;
+    }
+    Ok(())
+}
+
+//descompacta um .huff (ou gzip, zlib, ou um dos formatos antigos) de
+//`input` para `output`, usando uma thread por bloco no .huff. devolve quantos bytes foram escritos
+pub fn decompress<R: Read, W: Write>(input: R, output: W) -> Result<u64, HuffmanError> {
+    decompress_with_threads(input, output, default_threads())
+}
+
+pub fn decompress_with_threads<R: Read, W: Write>(
+    input: R,
+    output: W,
+    threads: usize,
+) -> Result<u64, HuffmanError> {
+    decompress_with(input, output, threads, &[])
+}
+
+//dicionario pedido pelo cabeçalho, entre os fornecidos
+fn dicionario(
+    header: &Header,
+    dictionaries: &[Arc<Dictionary>],
+) -> Result<Option<Arc<Dictionary>>, HuffmanError> {
+    let Some(id) = header.dictionary else {
+        return Ok(None);
+    };
+    match dictionaries.iter().find(|dict| dict.id() == id) {
+        Some(dict) if dict.alphabet() == header.alphabet => Ok(Some(dict.clone())),
+        Some(_) => Err(HuffmanError::Corrupt("alfabeto diferente do dicionario")),
+        None => Err(HuffmanError::MissingDictionary(id)),
+    }
+}
+
+//como decompress_with_threads, com os dicionarios que os arquivos do
+//método Dictionary podem pedir
+pub fn decompress_with<R: Read, W: Write>(
+    input: R,
+    output: W,
+    threads: usize,
+    dictionaries: &[Arc<Dictionary>],
+) -> Result<u64, HuffmanError> {
+    let mut input = BufReader::new(input);
+    let mut output = BufWriter::new(output);
+
+    let mut magic = Vec::with_capacity(4);
+    input.by_ref().take(4).read_to_end(&mut magic)?;
+    if magic != MAGIC {
+        //gzip e zlib também são lidos aos poucos
+        if let Some(wrapper) = deflate::detect(&magic) {
+            let input = io::Cursor::new(magic).chain(input);
+            let total = deflate::decompress(input, &mut output, wrapper)?;
+            output.flush()?;
+            return Ok(total);
+        }
+        //os formatos antigos não têm assinatura nem blocos: só dá para
+        //reconhecê-los com o arquivo inteiro em memoria
+        input.read_to_end(&mut magic)?;
+        let result = match container::detect(&magic) {
+            Some(container::Format::LegacyTree) => legacy::ler_arvore(&magic)?,
+            Some(container::Format::LegacyFreqTable) => legacy::ler_tabela(&magic)?,
+            _ => return Err(HuffmanError::UnknownFormat),
+        };
+        output.write_all(&result)?;
+        output.flush()?;
+        return Ok(result.len() as u64);
+    }
+
+    let header = container::read_header(&mut input)?;
+    let dict = dicionario(&header, dictionaries)?;
+    let mut crc = Crc32::new();
+    let mut total = 0u64;
+    if header.method == Method::Adaptive {
+        let mut out = CrcWriter { inner: &mut output, crc: &mut crc };
+        let mut model = AdaptiveModel::new(header.alphabet);
+        let mut numero = 0;
+        while let Some(n) =
+            container::read_adaptive_block(&mut input, &mut model, header.alphabet, &mut out)
+                .map_err(no_bloco(numero, total))?
+        {
+            total += n;
+            numero += 1;
+        }
+    } else {
+        let mut out = CrcWriter { inner: &mut output, crc: &mut crc };
+        let mut lote = Vec::new();
+        //o payload vem seguido do CRC do bloco quando o cabeçalho pede
+        let extra = if header.block_crc { 4 } else { 0 };
+        let mut numero = 0;
+        loop {
+            let erro = no_bloco(numero, total);
+            let Some(mut block) = container::read_block_header(&mut input, &header).map_err(erro)?
+            else {
+                break;
+            };
+            block.dictionary = dict.clone();
+            let raw_len = block.raw_len;
+            if block.payload_len > MAX_PARALLEL_BLOCK || block.raw_len > MAX_PARALLEL_BLOCK {
+                decodificar_lote(&mut lote, &mut out)?;
+                container::decode_block_checked(&block, &mut input, &header, &mut out)
+                    .map_err(erro)?;
+            } else {
+                let mut payload = Vec::with_capacity((block.payload_len + extra) as usize);
+                input.by_ref().take(block.payload_len + extra).read_to_end(&mut payload)?;
+                if payload.len() as u64 != block.payload_len + extra {
+                    return Err(erro(HuffmanError::Truncated));
+                }
+                lote.push((block, payload, header, (numero, total)));
+                if lote.len() >= threads.max(1) {
+                    decodificar_lote(&mut lote, &mut out)?;
+                }
+            }
+            total += raw_len;
+            numero += 1;
+        }
+        decodificar_lote(&mut lote, &mut out)?;
+    }
+    container::read_end(&mut input, &header, total, crc)?;
+    output.flush()?;
+    Ok(total)
+}
+
+//leitura e escrita posicionais: as threads da descompactação paralela
+//dividem os mesmos arquivos sem disputar a posição atual
+#[cfg(unix)]
+fn ler_em(file: &File, buf: &mut [u8], pos: u64) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, pos)
+}
+
+#[cfg(unix)]
+fn escrever_em(file: &File, buf: &[u8], pos: u64) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.write_all_at(buf, pos)
+}
+
+#[cfg(windows)]
+fn ler_em(file: &File, mut buf: &mut [u8], mut pos: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, pos) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                pos += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn escrever_em(file: &File, mut buf: &[u8], mut pos: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_write(buf, pos) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                buf = &buf[n..];
+                pos += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+//sem E/S posicional o decompress_file fica sempre no caminho em sequencia
+#[cfg(not(any(unix, windows)))]
+fn ler_em(_: &File, _: &mut [u8], _: u64) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn escrever_em(_: &File, _: &[u8], _: u64) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+//blocos que uma thread da descompactação paralela lê e escreve: os de
+//número `primeiro`, `primeiro + passo`... do indice
+type Faixa = (File, File, Arc<SeekIndex>, Option<Arc<Dictionary>>, (usize, usize));
+
+//decodifica os blocos da faixa direto na posição deles na saida. devolve o
+//CRC de cada bloco, ou o número do bloco que falhou e o erro
+fn decodificar_faixa(
+    (input, output, index, dict, (primeiro, passo)): Faixa,
+) -> Result<Vec<u32>, (usize, HuffmanError)> {
+    let mut crcs = Vec::new();
+    let mut bloco = Vec::new();
+    let mut out = Vec::new();
+    for i in (primeiro..index.points.len() - 1).step_by(passo) {
+        let ((inicio, original), (fim, proximo)) = (index.points[i], index.points[i + 1]);
+        let erro = no_bloco(i as u64, original);
+        let mut decodificar = || -> Result<(), HuffmanError> {
+            bloco.resize((fim - inicio) as usize, 0);
+            ler_em(&input, &mut bloco, inicio)?;
+            let mut resto = bloco.as_slice();
+            let mut block = container::read_block_header(&mut resto, &index.header)?
+                .ok_or(HuffmanError::Corrupt("indice aponta além dos blocos"))?;
+            if block.raw_len != proximo - original {
+                return Err(HuffmanError::Corrupt("indice não confere com os blocos"));
+            }
+            block.dictionary = dict.clone();
+            out.clear();
+            container::decode_block_checked(&block, &mut resto, &index.header, &mut out)?;
+            if !resto.is_empty() {
+                return Err(HuffmanError::Corrupt("indice não confere com os blocos"));
+            }
+            escrever_em(&output, &out, original)?;
+            Ok(())
+        };
+        decodificar().map_err(|e| (i, erro(e)))?;
+        crcs.push(crc32(&out));
+    }
+    Ok(crcs)
+}
+
+//descompacta de um arquivo para outro. com o indice de busca (a tabela de
+//posições dos blocos) cada thread decodifica uma parte dos blocos e escreve
+//o resultado direto na posição dele na saida, sem esperar os anteriores;
+//o CRC do arquivo inteiro sai dos CRCs dos blocos (crc32::combine). sem
+//indice, com uma thread só ou com blocos grandes demais para a memoria, cai
+//no decompress_with, que lê o arquivo em sequencia
+pub fn decompress_file(
+    input: &File,
+    output: &File,
+    threads: usize,
+    dictionaries: &[Arc<Dictionary>],
+) -> Result<u64, HuffmanError> {
+    let mut leitor = input;
+    let index = match SeekIndex::load(&mut leitor) {
+        Ok(index) => Some(index),
+        Err(HuffmanError::NoIndex | HuffmanError::UnknownFormat) => None,
+        Err(e) => return Err(e),
+    };
+    let grandes = index.as_ref().is_some_and(|index| {
+        index.points.windows(2).any(|par| {
+            par[1].0.saturating_sub(par[0].0) > MAX_PARALLEL_BLOCK
+                || par[1].1.saturating_sub(par[0].1) > MAX_PARALLEL_BLOCK
+        })
+    });
+    let index = match index {
+        Some(index) if threads > 1 && !grandes && cfg!(any(unix, windows)) => index,
+        _ => {
+            leitor.seek(SeekFrom::Start(0))?;
+            output.set_len(0)?;
+            return decompress_with(leitor, output, threads, dictionaries);
+        }
+    };
+    let dict = dicionario(&index.header, dictionaries)?;
+    if index.points.windows(2).any(|par| par[1].0 < par[0].0 || par[1].1 < par[0].1) {
+        return Err(HuffmanError::Corrupt("indice fora de ordem"));
+    }
+    let total = index.len();
+    output.set_len(total)?;
+
+    let blocos = index.points.len() - 1;
+    let threads = threads.min(blocos).max(1);
+    let index = Arc::new(index);
+    let mut faixas = Vec::with_capacity(threads);
+    for primeiro in 0..threads {
+        let arquivos = (input.try_clone()?, output.try_clone()?);
+        faixas.push((arquivos.0, arquivos.1, index.clone(), dict.clone(), (primeiro, threads)));
+    }
+    //o erro que vale é o do primeiro bloco, como no caminho em sequencia
+    let mut crcs = vec![0u32; blocos];
+    let mut falha: Option<(usize, HuffmanError)> = None;
+    for (primeiro, resultado) in map_in_threads(faixas, decodificar_faixa).into_iter().enumerate() {
+        match resultado {
+            Ok(lista) => {
+                for (k, crc) in lista.into_iter().enumerate() {
+                    crcs[primeiro + k * threads] = crc;
+                }
+            }
+            Err((i, e)) if falha.as_ref().is_none_or(|(j, _)| i < *j) => falha = Some((i, e)),
+            Err(_) => {}
+        }
+    }
+    if let Some((_, e)) = falha {
+        return Err(e);
+    }
+
+    //o marcador de fim (varint 0) e o CRC estão no ultimo ponto do indice
+    let mut trailer = [0u8; 5];
+    ler_em(input, &mut trailer, index.points[blocos].0)?;
+    if trailer[0] != 0 {
+        return Err(HuffmanError::Corrupt("indice não confere com os blocos"));
+    }
+    if let Some(expected) = index.header.original_len {
+        if total != expected {
+            return Err(HuffmanError::LengthMismatch { expected, found: total });
+        }
+    }
+    let mut found = crc32(&[]);
+    for (i, crc) in crcs.into_iter().enumerate() {
+        found = crc32::combine(found, crc, index.points[i + 1].1 - index.points[i].1);
+    }
+    let expected = u32::from_be_bytes(trailer[1..].try_into().unwrap());
+    if expected != found {
+        return Err(HuffmanError::ChecksumMismatch { expected, found });
+    }
+    Ok(total)
+}
+
+//lê `len` bytes a partir da posição `offset` do original usando o indice:
+//decodifica só os blocos que cobrem o trecho. o CRC do arquivo inteiro não
+//é conferido, só os dos blocos lidos (quando o arquivo tem). perto do fim
+//devolve menos bytes, e nenhum depois dele
+pub fn read_at<R: Read + Seek>(
+    input: R,
+    index: &SeekIndex,
+    offset: u64,
+    len: usize,
+    dictionaries: &[Arc<Dictionary>],
+) -> Result<Vec<u8>, HuffmanError> {
+    let dict = dicionario(&index.header, dictionaries)?;
+    let fim = offset.saturating_add(len as u64).min(index.len());
+    if offset >= fim {
+        return Ok(Vec::new());
+    }
+    //ultimo ponto que começa antes de `offset`; o primeiro é sempre 0
+    let i = index
+        .points
+        .partition_point(|p| p.1 <= offset)
+        .checked_sub(1)
+        .ok_or(HuffmanError::Corrupt("indice não começa no 0"))?;
+    let (inicio, mut posicao) = index.points[i];
+    let mut input = BufReader::new(input);
+    input.seek(SeekFrom::Start(inicio))?;
+
+    let mut out = Vec::with_capacity((fim - offset) as usize);
+    let mut bloco = Vec::new();
+    let mut numero = i as u64;
+    while posicao < fim {
+        let erro = no_bloco(numero, posicao);
+        let proximo = index.points.get(numero as usize + 1).map(|p| p.1);
+        let mut block = container::read_block_header(&mut input, &index.header)
+            .map_err(erro)?
+            .ok_or(HuffmanError::Corrupt("indice aponta além dos blocos"))?;
+        if proximo.and_then(|p| p.checked_sub(posicao)) != Some(block.raw_len) {
+            return Err(erro(HuffmanError::Corrupt("indice não confere com os blocos")));
+        }
+        block.dictionary = dict.clone();
+        bloco.clear();
+        container::decode_block_checked(&block, &mut input, &index.header, &mut bloco)
+            .map_err(erro)?;
+        numero += 1;
+        //o bloco tem raw_len bytes e cobre posicao..proximo, que contém offset
+        let de = offset.saturating_sub(posicao) as usize;
+        let ate = fim.saturating_sub(posicao).min(bloco.len() as u64) as usize;
+        out.extend_from_slice(bloco.get(de..ate).ok_or(HuffmanError::Corrupt("bloco curto"))?);
+        posicao += block.raw_len;
+    }
+    Ok(out)
+}
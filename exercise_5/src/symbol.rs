@@ -0,0 +1,259 @@
+//Huffman sobre qualquer tipo de simbolo (huffman::Symbol): leituras u16 de
+//sensores, opcodes de um enum, strings internadas. a arvore, os tamanhos
+//limitados e os códigos canônicos são os mesmos do .huff; o que muda é
+//como cada simbolo vai para a tabela, que fica a cargo de um
+//SymbolSerializer.
+//
+//tabela gravada (Codebook::write_table):
+//  varint  quantidade de simbolos
+//          cada simbolo, em ordem crescente: o simbolo no formato do
+//          serializer e 1 byte com o tamanho do código
+//buffer de encode:
+//          tabela
+//  varint  quantidade de simbolos codificados
+//          payload (códigos canônicos, bit mais significativo primeiro)
+//
+//decode recusa uma quantidade acima do max_symbols de quem chama e, se
+//todos os códigos têm 1 bit ou mais, acima dos bits do payload. com um
+//simbolo só o código tem tamanho 0 e só o max_symbols limita a quantidade
+//
+//o decodificador numera os simbolos pela ordem da tabela e usa a mesma
+//DecodeTable dos blocos do .huff sobre esses números
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::HuffmanError;
+use crate::huffman::{
+    canonical_codes, contar_frequencias, lengths_are_valid, limited_code_lengths_of,
+    read_varint, write_varint, DecodeTable, Symbol,
+};
+
+//grava e lê um simbolo na tabela. read_symbol precisa ler exatamente o
+//que write_symbol escreveu
+pub trait SymbolSerializer<S> {
+    fn write_symbol(&self, sym: &S, out: &mut Vec<u8>);
+    fn read_symbol<R: Read + ?Sized>(&self, input: &mut R) -> Result<S, HuffmanError>;
+}
+
+//inteiros sem sinal (u8, u16, u32, u64) em varint
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Varint;
+
+impl<S: Copy + Into<u64> + TryFrom<u64>> SymbolSerializer<S> for Varint {
+    fn write_symbol(&self, sym: &S, out: &mut Vec<u8>) {
+        write_varint((*sym).into(), out);
+    }
+
+    fn read_symbol<R: Read + ?Sized>(&self, input: &mut R) -> Result<S, HuffmanError> {
+        S::try_from(read_varint(input)?).map_err(|_| HuffmanError::Corrupt("simbolo fora do tipo"))
+    }
+}
+
+//maior string aceita na tabela
+const MAX_STRING: u64 = 1 << 20;
+
+//strings (String, Arc<str>, Rc<str>...) como varint com o tamanho e os
+//bytes UTF-8
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Utf8;
+
+impl<S: AsRef<str> + for<'a> From<&'a str>> SymbolSerializer<S> for Utf8 {
+    fn write_symbol(&self, sym: &S, out: &mut Vec<u8>) {
+        let bytes = sym.as_ref().as_bytes();
+        write_varint(bytes.len() as u64, out);
+        out.extend_from_slice(bytes);
+    }
+
+    fn read_symbol<R: Read + ?Sized>(&self, input: &mut R) -> Result<S, HuffmanError> {
+        let len = read_varint(input)?;
+        if len > MAX_STRING {
+            return Err(HuffmanError::Corrupt("string grande demais na tabela"));
+        }
+        let mut bytes = vec![0u8; len as usize];
+        input.read_exact(&mut bytes)?;
+        let texto = std::str::from_utf8(&bytes).map_err(|_| HuffmanError::InvalidUtf8)?;
+        Ok(S::from(texto))
+    }
+}
+
+//tamanhos e códigos canônicos de um alfabeto de simbolos S
+#[derive(Debug, Clone)]
+pub struct Codebook<S: Symbol> {
+    //em ordem crescente de simbolo
+    lengths: Vec<(S, u8)>,
+    codes: HashMap<S, (u64, u8)>,
+}
+
+impl<S: Symbol> Codebook<S> {
+    //códigos de Huffman das frequencias, nenhum maior que `max_code_len`
+    pub fn from_frequencies(freqs: &HashMap<S, u64>, max_code_len: u8) -> Self {
+        let lengths = limited_code_lengths_of(freqs, max_code_len);
+        let codes = canonical_codes(&lengths);
+        Codebook { lengths, codes }
+    }
+
+    //a partir de tamanhos prontos, que precisam formar um código de prefixo.
+    //vazio vale: é a tabela de uma entrada vazia
+    pub fn from_lengths(mut lengths: Vec<(S, u8)>) -> Result<Self, HuffmanError> {
+        lengths.sort_unstable();
+        let repetido = lengths.windows(2).any(|par| par[0].0 == par[1].0);
+        if repetido || (!lengths.is_empty() && !lengths_are_valid(&lengths)) {
+            return Err(HuffmanError::MalformedTree("código de prefixo incompleto ou repetido"));
+        }
+        let codes = canonical_codes(&lengths);
+        Ok(Codebook { lengths, codes })
+    }
+
+    pub fn lengths(&self) -> &[(S, u8)] {
+        &self.lengths
+    }
+
+    //(código, tamanho), com o código nos bits menos significativos
+    pub fn code(&self, sym: &S) -> Option<(u64, u8)> {
+        self.codes.get(sym).copied()
+    }
+
+    pub fn encode<W: Write>(
+        &self,
+        simbolos: &[S],
+        bits: &mut BitWriter<W>,
+    ) -> Result<(), HuffmanError> {
+        for s in simbolos {
+            let (code, len) = self.code(s).ok_or(HuffmanError::Corrupt("simbolo fora da tabela"))?;
+            bits.write_bits(code, len as u32)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_table<Z: SymbolSerializer<S>>(&self, serializer: &Z, out: &mut Vec<u8>) {
+        write_varint(self.lengths.len() as u64, out);
+        for (sym, len) in &self.lengths {
+            serializer.write_symbol(sym, out);
+            out.push(*len);
+        }
+    }
+
+    //lê uma tabela gravada por write_table; os simbolos precisam estar em
+    //ordem crescente e sem repetição
+    pub fn read_table<Z: SymbolSerializer<S>, R: Read + ?Sized>(
+        serializer: &Z,
+        input: &mut R,
+    ) -> Result<Self, HuffmanError> {
+        let n = read_varint(input)?;
+        if n > u32::MAX as u64 {
+            return Err(HuffmanError::MalformedTree("tabela com simbolos demais"));
+        }
+        let mut lengths: Vec<(S, u8)> = Vec::with_capacity(n.min(1 << 16) as usize);
+        for _ in 0..n {
+            let sym = serializer.read_symbol(input)?;
+            if lengths.last().is_some_and(|(anterior, _)| *anterior >= sym) {
+                return Err(HuffmanError::MalformedTree("simbolos fora de ordem ou repetidos"));
+            }
+            let mut len = [0u8; 1];
+            input.read_exact(&mut len)?;
+            lengths.push((sym, len[0]));
+        }
+        Codebook::from_lengths(lengths)
+    }
+
+    pub fn decoder(&self) -> SymbolDecoder<S> {
+        let numeros: Vec<(u32, u8)> =
+            self.lengths.iter().enumerate().map(|(i, (_, len))| (i as u32, *len)).collect();
+        SymbolDecoder {
+            table: DecodeTable::new(&numeros),
+            symbols: self.lengths.iter().map(|(s, _)| s.clone()).collect(),
+        }
+    }
+}
+
+//decodificador de um Codebook
+pub struct SymbolDecoder<S> {
+    table: DecodeTable,
+    symbols: Vec<S>,
+}
+
+impl<S: Clone> SymbolDecoder<S> {
+    //None se os dados acabarem no meio de um código
+    pub fn decode<R: Read>(&self, bits: &mut BitReader<R>) -> io::Result<Option<S>> {
+        Ok(self.table.decode(bits)?.map(|i| self.symbols[i as usize].clone()))
+    }
+}
+
+//codifica `simbolos` num buffer autocontido, com a tabela gravada pelo
+//serializer
+pub fn encode<S: Symbol, Z: SymbolSerializer<S>>(
+    simbolos: &[S],
+    serializer: &Z,
+    max_code_len: u8,
+) -> Result<Vec<u8>, HuffmanError> {
+    let codebook = Codebook::from_frequencies(&contar_frequencias(simbolos), max_code_len);
+    let mut out = Vec::new();
+    codebook.write_table(serializer, &mut out);
+    write_varint(simbolos.len() as u64, &mut out);
+    let mut bits = BitWriter::new(out);
+    codebook.encode(simbolos, &mut bits)?;
+    Ok(bits.finish()?)
+}
+
+//desfaz encode. `max_symbols` limita quantos simbolos o buffer pode pedir
+//(e a memoria da saida), já que um varint de poucos bytes pede bilhões
+pub fn decode<S: Symbol, Z: SymbolSerializer<S>>(
+    data: &[u8],
+    serializer: &Z,
+    max_symbols: u64,
+) -> Result<Vec<S>, HuffmanError> {
+    let mut input = data;
+    let codebook = Codebook::read_table(serializer, &mut input)?;
+    let n = read_varint(&mut input)?;
+    if n > max_symbols {
+        return Err(HuffmanError::Corrupt("mais simbolos que max_symbols"));
+    }
+    //cada código de 1 bit ou mais gasta pelo menos um bit do payload
+    let minimo = codebook.lengths.iter().map(|&(_, len)| len).min();
+    if minimo != Some(0) && n > input.len() as u64 * 8 {
+        return Err(HuffmanError::Corrupt("mais simbolos que bits no payload"));
+    }
+    let decoder = codebook.decoder();
+    let mut bits = BitReader::new(input);
+    let mut out = Vec::with_capacity(n.min(1 << 20) as usize);
+    for _ in 0..n {
+        let sym = decoder.decode(&mut bits)?.ok_or(HuffmanError::Truncated)?;
+        out.push(sym);
+    }
+    Ok(out)
+}
+
+//serializer feito de duas funções, para quem não quer declarar um tipo
+pub struct FnSerializer<S, W, R> {
+    write: W,
+    read: R,
+    simbolo: PhantomData<fn() -> S>,
+}
+
+impl<S, W, R> FnSerializer<S, W, R>
+where
+    W: Fn(&S, &mut Vec<u8>),
+    R: Fn(&mut dyn Read) -> Result<S, HuffmanError>,
+{
+    pub fn new(write: W, read: R) -> Self {
+        FnSerializer { write, read, simbolo: PhantomData }
+    }
+}
+
+impl<S, W, R> SymbolSerializer<S> for FnSerializer<S, W, R>
+where
+    W: Fn(&S, &mut Vec<u8>),
+    R: Fn(&mut dyn Read) -> Result<S, HuffmanError>,
+{
+    fn write_symbol(&self, sym: &S, out: &mut Vec<u8>) {
+        (self.write)(sym, out)
+    }
+
+    fn read_symbol<I: Read + ?Sized>(&self, input: &mut I) -> Result<S, HuffmanError> {
+        //&mut I é Read e tem tamanho, mesmo quando I não tem
+        let mut input = input;
+        (self.read)(&mut input)
+    }
+}
@@ -0,0 +1,209 @@
+//alfabeto de palavras: o bloco é quebrado em palavras e separadores (ou nos
+//tokens dados pelo usuário) e cada token que se repete vira um simbolo só.
+//em texto de verdade isso pega o que o Huffman de ordem 0 sobre bytes
+//deixa na mesa, já que "que" custa um código em vez de três.
+//
+//os simbolos 0..=255 são bytes literais e 256 + i é o token i do
+//vocabulario do bloco. entram no vocabulario os tokens de 2 bytes ou mais
+//que aparecem pelo menos duas vezes no bloco; o resto é escrito byte a
+//byte, então qualquer entrada faz o round-trip. a arvore é montada direto
+//sobre os tokens (Huffman<Peca>) e só depois eles viram números.
+//
+//vocabulario gravado no bloco:
+//  varint  quantidade de tokens
+//          cada token: varint tamanho e os bytes, em ordem crescente
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::bits::BitReader;
+use crate::error::HuffmanError;
+use crate::huffman::{
+    contar_frequencias, limited_code_lengths_of, read_varint, write_varint, DecodeTable,
+    MAX_SYMBOLS,
+};
+
+//primeiro simbolo do vocabulario
+pub const FIRST_TOKEN: u32 = 256;
+
+//simbolo antes de virar número: byte literal ou token do vocabulario. os
+//literais vêm antes, então a ordem dos tamanhos já é a dos números
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Peca<'a> {
+    Literal(u8),
+    Palavra(&'a [u8]),
+}
+
+//letras, digitos e qualquer byte de um caractere UTF-8 de mais de um byte
+fn de_palavra(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b >= 0x80
+}
+
+//quebra `data` em tokens que, juntos, são exatamente `data`. sem lista, são
+//as sequencias alternadas de bytes de palavra e de separadores; com lista, o
+//maior token da lista que começa em cada posição, ou um byte solto
+pub fn split<'a>(data: &'a [u8], tokens: Option<&[Vec<u8>]>) -> Vec<&'a [u8]> {
+    let mut out = Vec::with_capacity(data.len() / 3);
+    let Some(tokens) = tokens else {
+        let mut inicio = 0;
+        for i in 1..=data.len() {
+            if i == data.len() || de_palavra(data[i]) != de_palavra(data[inicio]) {
+                out.push(&data[inicio..i]);
+                inicio = i;
+            }
+        }
+        return out;
+    };
+    let trie = Trie::new(tokens);
+    let mut i = 0;
+    while i < data.len() {
+        let len = trie.maior_prefixo(&data[i..]).unwrap_or(1);
+        out.push(&data[i..i + len]);
+        i += len;
+    }
+    out
+}
+
+//trie dos tokens da lista: cada posição da entrada desce só enquanto algum
+//token continua, em vez de testar a lista inteira
+struct Trie {
+    //(nó, byte) -> filho; o nó 0 é a raiz
+    filhos: HashMap<(u32, u8), u32>,
+    //o caminho até o nó é um token
+    fim: Vec<bool>,
+}
+
+impl Trie {
+    fn new(tokens: &[Vec<u8>]) -> Trie {
+        let mut trie = Trie { filhos: HashMap::new(), fim: vec![false] };
+        for token in tokens {
+            let mut no = 0;
+            for &b in token {
+                let novo = trie.fim.len() as u32;
+                no = *trie.filhos.entry((no, b)).or_insert(novo);
+                if no == novo {
+                    trie.fim.push(false);
+                }
+            }
+            trie.fim[no as usize] = true;
+        }
+        trie
+    }
+
+    //tamanho do maior token de 2 bytes ou mais no inicio de `data`
+    fn maior_prefixo(&self, data: &[u8]) -> Option<usize> {
+        let mut no = 0;
+        let mut maior = None;
+        for (i, &b) in data.iter().enumerate() {
+            match self.filhos.get(&(no, b)) {
+                Some(&filho) => no = filho,
+                None => break,
+            }
+            if i >= 1 && self.fim[no as usize] {
+                maior = Some(i + 1);
+            }
+        }
+        maior
+    }
+}
+
+//o que o bloco grava: vocabulario, tamanhos de código por simbolo e a
+//sequencia de simbolos
+pub struct Model<'a> {
+    pub vocabulary: Vec<&'a [u8]>,
+    pub lengths: Vec<(u32, u8)>,
+    pub symbols: Vec<u32>,
+}
+
+pub fn model<'a>(data: &'a [u8], tokens: Option<&[Vec<u8>]>, max_code_len: u8) -> Model<'a> {
+    let pecas = split(data, tokens);
+    let contagem = contar_frequencias(&pecas);
+    let mut simbolos = Vec::with_capacity(pecas.len());
+    for p in pecas {
+        if p.len() >= 2 && contagem[p] >= 2 {
+            simbolos.push(Peca::Palavra(p));
+        } else {
+            simbolos.extend(p.iter().map(|&b| Peca::Literal(b)));
+        }
+    }
+    let lengths = limited_code_lengths_of(&contar_frequencias(&simbolos), max_code_len);
+
+    let mut vocabulario = Vec::new();
+    let mut numeros = HashMap::with_capacity(lengths.len());
+    let lengths = lengths
+        .into_iter()
+        .map(|(p, len)| {
+            let sym = match p {
+                Peca::Literal(b) => b as u32,
+                Peca::Palavra(w) => {
+                    vocabulario.push(w);
+                    FIRST_TOKEN + vocabulario.len() as u32 - 1
+                }
+            };
+            numeros.insert(p, sym);
+            (sym, len)
+        })
+        .collect();
+    let symbols = simbolos.iter().map(|p| numeros[p]).collect();
+    Model { vocabulary: vocabulario, lengths, symbols }
+}
+
+pub fn write_vocabulary(vocabulario: &[&[u8]], out: &mut Vec<u8>) {
+    write_varint(vocabulario.len() as u64, out);
+    for token in vocabulario {
+        write_varint(token.len() as u64, out);
+        out.extend_from_slice(token);
+    }
+}
+
+//lê o vocabulario de um bloco de `raw_len` bytes: os tokens não podem somar
+//mais que o bloco
+pub fn read_vocabulary<R: Read>(
+    input: &mut R,
+    raw_len: u64,
+) -> Result<Vec<Vec<u8>>, HuffmanError> {
+    let n = read_varint(input)?;
+    if n > MAX_SYMBOLS - FIRST_TOKEN as u64 {
+        return Err(HuffmanError::Corrupt("vocabulario grande demais"));
+    }
+    let mut vocabulario = Vec::with_capacity(n.min(1 << 16) as usize);
+    let mut restante = raw_len;
+    for _ in 0..n {
+        let len = read_varint(input)?;
+        if len < 2 || len > restante {
+            return Err(HuffmanError::Corrupt("token com tamanho invalido"));
+        }
+        restante -= len;
+        let mut token = vec![0u8; len as usize];
+        input.read_exact(&mut token)?;
+        vocabulario.push(token);
+    }
+    Ok(vocabulario)
+}
+
+//lê simbolos até completar `raw_len` bytes, trocando os do vocabulario
+//pelos tokens
+pub fn decode<R: Read, W: Write>(
+    raw_len: u64,
+    vocabulario: &[Vec<u8>],
+    table: &DecodeTable,
+    bits: &mut BitReader<R>,
+    out: &mut W,
+) -> Result<(), HuffmanError> {
+    let mut buf: Vec<u8> = Vec::with_capacity(raw_len.min(1 << 24) as usize);
+    while (buf.len() as u64) < raw_len {
+        let sym = table.decode(bits)?.ok_or(HuffmanError::Corrupt("payload menor que o bloco"))?;
+        if sym < FIRST_TOKEN {
+            buf.push(sym as u8);
+            continue;
+        }
+        let token = vocabulario
+            .get((sym - FIRST_TOKEN) as usize)
+            .ok_or(HuffmanError::Corrupt("token fora do vocabulario"))?;
+        if (buf.len() + token.len()) as u64 > raw_len {
+            return Err(HuffmanError::Corrupt("token passa do fim do bloco"));
+        }
+        buf.extend_from_slice(token);
+    }
+    out.write_all(&buf)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//Huffman adaptativo com varios blocos: cada read da entrada vira um bloco e
+//o modelo continua de um bloco para o outro, então a ordem dos blocos e o
+//corte entre eles (inclusive no meio de um caractere UTF-8) importam
+use huffman::{info, Alphabet, Decoder, Encoder, Method, Options};
+
+mod common;
+use common::{ruido, AosPoucos};
+
+fn adaptativo(alphabet: Alphabet) -> Encoder {
+    Encoder::new(Options { method: Method::Adaptive, alphabet, ..Default::default() })
+}
+
+//texto que muda de distribuição no meio: o modelo precisa se adaptar
+fn texto() -> Vec<u8> {
+    let mut out = "o modelo começa com poucas folhas e cresce. ".repeat(2_000).into_bytes();
+    out.extend("ΑΒΓΔ ação ✓ €uro — ".repeat(3_000).bytes());
+    out.extend(b"0123456789".repeat(5_000));
+    out
+}
+
+#[test]
+fn varios_blocos_fazem_ida_e_volta() {
+    let data = texto();
+    for alphabet in [Alphabet::Bytes, Alphabet::Chars] {
+        //um bloco por read da entrada: 997 bytes corta caracteres no meio
+        for passo in [997, 1 << 16, usize::MAX] {
+            let mut arquivo = Vec::new();
+            let total = adaptativo(alphabet)
+                .compress(AosPoucos::new(&data, passo), &mut arquivo)
+                .unwrap();
+            assert_eq!(total, data.len() as u64);
+            let resumo = info(arquivo.as_slice()).unwrap();
+            assert_eq!(resumo.original_len, Some(data.len() as u64));
+            assert!(resumo.blocks > 1, "{:?} {}: {} blocos", alphabet, passo, resumo.blocks);
+            assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+        }
+    }
+    //sem estrutura nenhuma e um byte por bloco
+    let data = ruido(3_000);
+    let mut arquivo = Vec::new();
+    adaptativo(Alphabet::Bytes).compress(AosPoucos::new(&data, 1), &mut arquivo).unwrap();
+    assert_eq!(info(arquivo.as_slice()).unwrap().blocks, 3_000);
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+}
+
+#[test]
+fn blocos_dependem_dos_anteriores() {
+    let data = texto();
+    let arquivo = adaptativo(Alphabet::Bytes).compress_bytes(&data).unwrap();
+    assert!(info(arquivo.as_slice()).unwrap().blocks > 2);
+    assert!(arquivo.len() < data.len() * 3 / 4, "{}", arquivo.len());
+
+    //um bit trocado no começo estraga o modelo dos blocos seguintes: ou vira
+    //erro, ou o CRC do fim não confere
+    let mut estragado = arquivo.clone();
+    estragado[40] ^= 0x08;
+    assert!(Decoder::new().decompress_bytes(&estragado).is_err());
+    //arquivo cortado no meio de um bloco
+    assert!(Decoder::new().decompress_bytes(&arquivo[..arquivo.len() / 2]).is_err());
+}
@@ -0,0 +1,86 @@
+//relatorio de estatisticas: entropia e códigos de uma distribuição
+//conhecida, e os mesmos números lendo o .huff gravado
+use std::sync::Arc;
+
+use huffman::analyze::{analyze, analyze_with};
+use huffman::huffman::DEFAULT_MAX_CODE_LEN;
+use huffman::{Alphabet, Decoder, Dictionary, Encoder, HuffmanError, Options};
+
+//a: 1/2, b: 1/4, c e d: 1/8. o Huffman atinge a entropia (1.75 bits)
+fn diadico() -> Vec<u8> {
+    b"aaaabbcd".repeat(1000)
+}
+
+#[test]
+fn entropia_e_codigos() {
+    let analise = analyze(&diadico(), Alphabet::Bytes).unwrap();
+    assert_eq!(analise.symbols, 8000);
+    assert!((analise.entropy - 1.75).abs() < 1e-9);
+    assert!((analise.average_code_len - 1.75).abs() < 1e-9);
+    let tamanhos: Vec<_> =
+        analise.table.iter().map(|l| (l.symbol as u8, l.count, l.code.len())).collect();
+    assert_eq!(tamanhos, vec![(b'a', 4000, 1), (b'b', 2000, 2), (b'c', 1000, 3), (b'd', 1000, 3)]);
+    //payload de 8000 * 1.75 bits, o resto é cabeçalho
+    assert_eq!(analise.compressed_len - analise.header_overhead, 1750);
+
+    let json = analise.to_json();
+    assert!(json.starts_with("{\"alphabet\":\"bytes\",\"from_huff\":false,\"symbols\":8000,"));
+    assert!(json.contains("\"entropy\":1.750000"));
+    assert!(json.contains("{\"symbol\":97,\"count\":4000,\"length\":1,\"code\":\""));
+}
+
+#[test]
+fn le_o_huff() {
+    let arquivo = Encoder::default().compress_bytes(&diadico()).unwrap();
+    let analise = analyze(&arquivo, Alphabet::Bytes).unwrap();
+    assert!(analise.from_huff);
+    assert_eq!(analise.original_len, 8000);
+    assert_eq!(analise.compressed_len, arquivo.len() as u64);
+    assert!((analise.entropy - 1.75).abs() < 1e-9);
+}
+
+#[test]
+fn codigos_limitados_como_no_arquivo() {
+    //contagens de Fibonacci: sem limite o simbolo mais raro teria 25 bits
+    let (mut a, mut b) = (1usize, 1usize);
+    let mut data = Vec::new();
+    for i in 0..26u8 {
+        data.extend(std::iter::repeat_n(b'a' + i, a));
+        (a, b) = (b, a + b);
+    }
+    let analise = analyze(&data, Alphabet::Bytes).unwrap();
+    assert_eq!(analise.table.len(), 26);
+    let maior = analise.table.iter().map(|l| l.code.len()).max().unwrap();
+    assert_eq!(maior, DEFAULT_MAX_CODE_LEN as usize);
+    //canonicos: em ordem de tamanho, cada código é o anterior + 1
+    let mut codigos: Vec<&str> = analise.table.iter().map(|l| l.code.as_str()).collect();
+    codigos.sort_by_key(|c| (c.len(), c.to_string()));
+    for par in codigos.windows(2) {
+        let (anterior, proximo) = (par[0], par[1]);
+        let valor = |c: &str| u64::from_str_radix(c, 2).unwrap();
+        let esperado = (valor(anterior) + 1) << (proximo.len() - anterior.len());
+        assert_eq!(esperado, valor(proximo));
+    }
+    //o tamanho medio bate com o payload que o compress gravou
+    let bits: u64 = analise.table.iter().map(|l| l.count * l.code.len() as u64).sum();
+    assert!((analise.average_code_len - bits as f64 / data.len() as f64).abs() < 1e-9);
+    assert_eq!(analise.compressed_len - analise.header_overhead, bits.div_ceil(8));
+}
+
+#[test]
+fn huff_com_dicionario() {
+    let amostras: Vec<String> = (0..50).map(|i| format!("evento {} ok\n", i)).collect();
+    let dict = Dictionary::train(&amostras, Alphabet::Bytes, 15).unwrap();
+    let options = Options { dictionary: Some(Arc::new(dict.clone())), ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(b"evento 77 ok\n").unwrap();
+
+    assert!(matches!(
+        analyze(&arquivo, Alphabet::Bytes),
+        Err(HuffmanError::MissingDictionary(_))
+    ));
+    let analise =
+        analyze_with(&arquivo, Alphabet::Bytes, &Decoder::new().with_dictionary(dict)).unwrap();
+    assert!(analise.from_huff);
+    assert_eq!(analise.original_len, 13);
+    assert_eq!(analise.compressed_len, arquivo.len() as u64);
+}
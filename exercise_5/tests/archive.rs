@@ -0,0 +1,142 @@
+//arquivo de arquivos: criar, listar e extrair tudo ou um membro só
+use std::fs;
+use std::io::Cursor;
+
+use huffman::archive::{self, EntryKind};
+use huffman::{HuffmanError, Method, Options};
+
+mod common;
+use common::temporario;
+
+#[test]
+fn diretorio_faz_ida_e_volta() {
+    let dir = temporario("archive");
+    let origem = dir.join("relatorios");
+    fs::create_dir_all(origem.join("2024")).unwrap();
+    fs::write(origem.join("a.txt"), "relatorio a\n".repeat(200)).unwrap();
+    fs::write(origem.join("2024/b.txt"), b"").unwrap();
+    fs::write(origem.join("2024/c.bin"), (0..=255u8).cycle().take(5000).collect::<Vec<_>>()).unwrap();
+
+    let options = Options { method: Method::Lz77, ..Default::default() };
+    let mut arquivo = Cursor::new(Vec::new());
+    archive::create(&mut arquivo, &[&origem], &options).unwrap();
+    let arquivo = arquivo.into_inner();
+
+    let entradas = archive::list(Cursor::new(&arquivo)).unwrap();
+    let nomes: Vec<_> = entradas.iter().map(|e| (e.path.as_str(), e.kind, e.size)).collect();
+    assert_eq!(
+        nomes,
+        vec![
+            ("relatorios", EntryKind::Dir, 0),
+            ("relatorios/2024", EntryKind::Dir, 0),
+            ("relatorios/2024/b.txt", EntryKind::File, 0),
+            ("relatorios/2024/c.bin", EntryKind::File, 5000),
+            ("relatorios/a.txt", EntryKind::File, 2400),
+        ]
+    );
+
+    let mut um = Vec::new();
+    archive::extract(Cursor::new(&arquivo), "relatorios/2024/c.bin", &mut um).unwrap();
+    assert_eq!(um, fs::read(origem.join("2024/c.bin")).unwrap());
+    assert!(matches!(
+        archive::extract(Cursor::new(&arquivo), "relatorios/x", Vec::new()),
+        Err(HuffmanError::NotFound(_))
+    ));
+
+    let destino = dir.join("destino");
+    archive::extract_all(Cursor::new(&arquivo), &destino).unwrap();
+    for nome in ["a.txt", "2024/b.txt", "2024/c.bin"] {
+        let extraido = destino.join("relatorios").join(nome);
+        assert_eq!(fs::read(&extraido).unwrap(), fs::read(origem.join(nome)).unwrap());
+        assert_eq!(
+            fs::metadata(&extraido).unwrap().modified().unwrap(),
+            archive::modified(entradas.iter().find(|e| e.path.ends_with(nome)).unwrap())
+        );
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn caminho_fora_do_destino_e_recusado() {
+    //entrada "../x" escrita à mão: diretorio sem dados
+    let mut arquivo = archive::MAGIC.to_vec();
+    arquivo.push(archive::VERSION);
+    arquivo.push(4);
+    arquivo.extend_from_slice(b"../x");
+    arquivo.push(1);
+    arquivo.extend_from_slice(&[0; 28]);
+    arquivo.push(0);
+
+    let dir = temporario("fora");
+    assert!(matches!(
+        archive::extract_all(Cursor::new(&arquivo), &dir.join("destino")),
+        Err(HuffmanError::Corrupt(_))
+    ));
+    assert!(!dir.join("x").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn setuid_so_com_bits_especiais() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temporario("setuid");
+    let origem = dir.join("origem");
+    fs::create_dir_all(origem.join("comum")).unwrap();
+    fs::write(origem.join("programa"), b"#!/bin/sh\n").unwrap();
+    fs::set_permissions(origem.join("programa"), fs::Permissions::from_mode(0o6755)).unwrap();
+    fs::set_permissions(origem.join("comum"), fs::Permissions::from_mode(0o1777)).unwrap();
+
+    let mut arquivo = Cursor::new(Vec::new());
+    let entradas = archive::create(&mut arquivo, &[&origem], &Options::default()).unwrap();
+    let arquivo = arquivo.into_inner();
+    //o arquivo guarda os bits como estavam
+    let modo = |nome: &str| entradas.iter().find(|e| e.path.ends_with(nome)).unwrap().mode;
+    assert_eq!((modo("programa"), modo("comum")), (0o6755, 0o1777));
+
+    let extraido = |destino: &std::path::Path, nome: &str| {
+        fs::metadata(destino.join("origem").join(nome)).unwrap().permissions().mode() & 0o7777
+    };
+    let destino = dir.join("padrao");
+    archive::extract_all(Cursor::new(&arquivo), &destino).unwrap();
+    assert_eq!(extraido(&destino, "programa"), 0o755);
+    assert_eq!(extraido(&destino, "comum"), 0o777);
+
+    let destino = dir.join("especiais");
+    archive::extract_all_with(Cursor::new(&arquivo), &destino, true).unwrap();
+    assert_eq!(extraido(&destino, "programa"), 0o6755);
+    assert_eq!(extraido(&destino, "comum"), 0o1777);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn diretorio_sem_nome_entra_pelo_conteudo() {
+    let dir = temporario("ponto");
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("a.txt"), "a\n".repeat(100)).unwrap();
+    fs::write(dir.join("sub/b.txt"), b"b").unwrap();
+
+    //como o tar c .: "sub/.." (e ".") não têm nome, entram só os filhos. o
+    //"." não dá para testar aqui sem mudar o diretorio atual dos outros testes
+    for caminho in [dir.join("sub").join(".."), dir.join("sub").join("..").join(".")] {
+        let mut arquivo = Cursor::new(Vec::new());
+        let entradas = archive::create(&mut arquivo, &[&caminho], &Options::default()).unwrap();
+        let nomes: Vec<_> = entradas.iter().map(|e| (e.path.as_str(), e.kind)).collect();
+        assert_eq!(
+            nomes,
+            vec![
+                ("a.txt", EntryKind::File),
+                ("sub", EntryKind::Dir),
+                ("sub/b.txt", EntryKind::File),
+            ],
+            "{}",
+            caminho.display()
+        );
+        let destino = dir.join("destino");
+        archive::extract_all(Cursor::new(arquivo.into_inner()), &destino).unwrap();
+        assert_eq!(fs::read(destino.join("sub/b.txt")).unwrap(), b"b");
+        fs::remove_dir_all(&destino).unwrap();
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
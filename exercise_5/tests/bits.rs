@@ -0,0 +1,107 @@
+//leitores de bits: o refill por palavras tem que dar o mesmo resultado que
+//byte a byte, venha o reader em pedaços de qualquer tamanho
+use std::collections::HashMap;
+
+use huffman::bits::{BitReader, BitWriter, LsbReader, LsbWriter};
+use huffman::crc32::crc32;
+use huffman::huffman::{canonical_codes, DecodeTable, Huffman, TABLE_BITS};
+
+mod common;
+use common::AosPoucos;
+
+//(valor, tamanho) variados, de 1 a 32 bits
+fn campos() -> Vec<(u64, u32)> {
+    (0..5_000u64)
+        .map(|i| (i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 7, 1 + (i % 32) as u32))
+        .collect()
+}
+
+#[test]
+fn msb_em_pedacos() {
+    let mut w = BitWriter::new(Vec::new());
+    for &(v, len) in &campos() {
+        w.write_bits(v, len).unwrap();
+    }
+    let data = w.finish().unwrap();
+    for passo in [1, 3, 8, 1000, usize::MAX] {
+        let mut bits = BitReader::new(AosPoucos::new(&data, passo));
+        for &(v, len) in &campos() {
+            let (lido, disponiveis) = bits.peek(len).unwrap();
+            assert_eq!((lido, disponiveis), (v & ((1 << len) - 1), len), "passo {}", passo);
+            bits.consume(len);
+        }
+        //o resto é o enchimento do ultimo byte
+        let total = bits.bits_read();
+        assert_eq!(total.div_ceil(8), data.len() as u64);
+        while bits.read_bit().unwrap().is_some() {}
+        assert_eq!(bits.bits_read(), data.len() as u64 * 8);
+    }
+}
+
+#[test]
+fn lsb_em_pedacos() {
+    let mut w = LsbWriter::new(Vec::new());
+    for &(v, len) in &campos() {
+        w.write_bits(v, len).unwrap();
+    }
+    w.align().unwrap();
+    w.write_bytes(b"cauda").unwrap();
+    let data = w.finish().unwrap();
+    for passo in [1, 3, 8, 1000, usize::MAX] {
+        let mut bits = LsbReader::new(AosPoucos::new(&data, passo));
+        for &(v, len) in &campos() {
+            assert_eq!(bits.read_bits(len).unwrap(), v & ((1 << len) - 1), "passo {}", passo);
+        }
+        bits.align();
+        let mut cauda = [0u8; 5];
+        bits.read_bytes(&mut cauda).unwrap();
+        assert_eq!(&cauda, b"cauda");
+        assert_eq!(bits.read_some(&mut cauda).unwrap(), 0);
+    }
+}
+
+#[test]
+fn tabela_com_codigos_longos() {
+    //contagens de Fibonacci: os simbolos raros passam de TABLE_BITS bits
+    let mut freqs = HashMap::new();
+    let (mut a, mut b) = (1u64, 1u64);
+    for s in 0..20u32 {
+        freqs.insert(s, a);
+        (a, b) = (b, a + b);
+    }
+    let lengths = Huffman::build_tree(&freqs).unwrap().code_lengths();
+    assert!(lengths.iter().any(|&(_, l)| l as u32 > TABLE_BITS));
+    let codes = canonical_codes(&lengths);
+    let simbolos: Vec<u32> = (0..3_000u32).map(|i| i * 7 % 20).collect();
+    let mut w = BitWriter::new(Vec::new());
+    for s in &simbolos {
+        let (code, len) = codes[s];
+        w.write_bits(code, len as u32).unwrap();
+    }
+    let data = w.finish().unwrap();
+
+    let table = DecodeTable::new(&lengths);
+    let mut bits = BitReader::new(AosPoucos::new(&data, 3));
+    for s in &simbolos {
+        assert_eq!(table.decode(&mut bits).unwrap(), Some(*s));
+    }
+    assert_eq!(bits.bits_read().div_ceil(8), data.len() as u64);
+}
+
+#[test]
+fn crc_por_palavras() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(b""), 0);
+    //todos os restos da divisão por 8
+    let data: Vec<u8> = (0..100u8).collect();
+    for n in 0..data.len() {
+        let mut c = !0u32;
+        for &b in &data[..n] {
+            c ^= b as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 { 0xEDB8_8320 ^ (c >> 1) } else { c >> 1 };
+            }
+        }
+        assert_eq!(crc32(&data[..n]), !c, "{}", n);
+    }
+}
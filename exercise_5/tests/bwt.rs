@@ -0,0 +1,34 @@
+//BWT: a transformada tem que se desfazer em entradas repetitivas e
+//periodicas, e o método inteiro tem que ganhar do Huffman de ordem 0 em texto
+use huffman::bwt::{inverse, transform};
+use huffman::{Decoder, Encoder, Method, Options};
+
+#[test]
+fn transformada_se_desfaz() {
+    let casos: Vec<Vec<u8>> = vec![
+        b"banana".to_vec(),
+        b"abababab".to_vec(),
+        b"mississippi".to_vec(),
+        vec![7; 1000],
+        (0..5000u32).map(|i| (i * i % 251) as u8).collect(),
+        b"a".to_vec(),
+    ];
+    for data in casos {
+        let (ultima, origem) = transform(&data);
+        assert_eq!(ultima.len(), data.len());
+        assert_eq!(inverse(&ultima, origem).unwrap(), data);
+    }
+    assert_eq!(transform(b"banana"), (b"annbaa".to_vec(), 4));
+}
+
+#[test]
+fn ganha_do_huffman_em_texto() {
+    let texto: Vec<u8> = (0..3000)
+        .flat_map(|i| format!("a entrada {} do manual descreve --nivel={}\n", i, i % 10).into_bytes())
+        .collect();
+    let huffman = Encoder::default().compress_bytes(&texto).unwrap();
+    let options = Options { method: Method::Bwt, block_size: 4 << 20, ..Default::default() };
+    let bwt = Encoder::new(options).compress_bytes(&texto).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&bwt).unwrap(), texto);
+    assert!(bwt.len() * 4 < huffman.len(), "{} {}", bwt.len(), huffman.len());
+}
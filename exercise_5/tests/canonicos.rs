@@ -0,0 +1,86 @@
+//códigos canônicos: os tamanhos bastam para remontar a tabela, então ela
+//não pode depender da ordem de iteração do HashMap nem de como os empates de
+//frequencia foram resolvidos
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use huffman::container::{self, MAGIC};
+use huffman::huffman::{canonical_codes, limited_code_lengths, write_code_lengths};
+use huffman::Encoder;
+
+#[test]
+fn mesmos_tamanhos_mesmos_codigos() {
+    //o exemplo da RFC 1951, 3.2.2
+    let lengths: Vec<(u32, u8)> =
+        "ABCDEFGH".chars().map(|c| c as u32).zip([3, 3, 3, 3, 3, 2, 4, 4]).collect();
+    let codes = canonical_codes(&lengths);
+    let esperado = [
+        ('A', 0b010, 3),
+        ('B', 0b011, 3),
+        ('C', 0b100, 3),
+        ('D', 0b101, 3),
+        ('E', 0b110, 3),
+        ('F', 0b00, 2),
+        ('G', 0b1110, 4),
+        ('H', 0b1111, 4),
+    ];
+    for (c, code, len) in esperado {
+        assert_eq!(codes[&(c as u32)], (code, len), "{}", c);
+    }
+
+    //a ordem em que os tamanhos chegam não muda nada
+    let mut invertidos = lengths.clone();
+    invertidos.reverse();
+    assert_eq!(canonical_codes(&invertidos), codes);
+    let mut embaralhados = lengths;
+    embaralhados.swap(0, 5);
+    embaralhados.swap(2, 7);
+    assert_eq!(canonical_codes(&embaralhados), codes);
+}
+
+#[test]
+fn empates_nao_mudam_a_tabela() {
+    //muitos simbolos com a mesma frequencia, inseridos em ordens diferentes
+    //(e cada HashMap com a sua semente)
+    let freqs: Vec<(u32, u64)> = (0..200u32).map(|s| (s * 7 % 256, 1 + (s % 4) as u64)).collect();
+    let mut tabelas = Vec::new();
+    for rodada in 0..8 {
+        let mut ordem = freqs.clone();
+        ordem.rotate_left(rodada * 25);
+        if rodada % 2 == 1 {
+            ordem.reverse();
+        }
+        let mapa: HashMap<u32, u64> = ordem.into_iter().collect();
+        for max_len in [15, 8] {
+            let lengths = limited_code_lengths(&mapa, max_len);
+            let mut tabela = Vec::new();
+            write_code_lengths(&lengths, &mut tabela);
+            tabelas.push((max_len, tabela));
+        }
+    }
+    for (max_len, tabela) in &tabelas {
+        assert_eq!(tabela, &tabelas[(*max_len == 8) as usize].1);
+    }
+}
+
+#[test]
+fn arquivo_igual_em_toda_execucao() {
+    let texto = b"abracadabra alakazam ".repeat(300);
+    let primeiro = Encoder::default().compress_bytes(&texto).unwrap();
+    for _ in 0..5 {
+        assert_eq!(Encoder::default().compress_bytes(&texto).unwrap(), primeiro);
+    }
+    //mesmas frequencias em outra ordem: a tabela no começo do bloco é a mesma
+    let mut trocado = texto.clone();
+    trocado.reverse();
+    let outro = Encoder::default().compress_bytes(&trocado).unwrap();
+    assert_eq!(tamanhos(&outro), tamanhos(&primeiro));
+}
+
+//tamanhos de código do primeiro bloco
+fn tamanhos(arquivo: &[u8]) -> Vec<(u32, u8)> {
+    let mut input = Cursor::new(arquivo);
+    input.set_position(MAGIC.len() as u64);
+    let header = container::read_header(&mut input).unwrap();
+    container::read_block_header(&mut input, &header).unwrap().unwrap().lengths
+}
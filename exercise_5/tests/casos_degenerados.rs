@@ -0,0 +1,117 @@
+//entradas vazias e com um simbolo só: precisam fazer o round-trip exato em
+//todos os modos e ter a representação descrita em container.rs
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use huffman::container::{MAGIC, UNKNOWN_LEN, VERSION};
+use huffman::crc32::crc32;
+use huffman::huffman::{limited_code_lengths, Huffman};
+use huffman::{Alphabet, Coder, Decoder, Encoder, HuffmanError, Method, Options, Wrapper};
+
+fn todas_as_opcoes() -> Vec<Options> {
+    let base = Options { threads: 2, ..Default::default() };
+    vec![
+        base.clone(),
+        Options { alphabet: Alphabet::Chars, ..base.clone() },
+        Options { method: Method::Adaptive, ..base.clone() },
+        Options { coder: Coder::Rans, ..base.clone() },
+        Options { rle: true, ..base.clone() },
+        Options { method: Method::Lz77, ..base.clone() },
+        Options { method: Method::Bwt, ..base.clone() },
+        Options { method: Method::Tokens, ..base.clone() },
+        Options { method: Method::Context, context_order: 2, ..base.clone() },
+        Options { wrapper: Some(Wrapper::Gzip), ..base.clone() },
+        Options { wrapper: Some(Wrapper::Zlib), ..base.clone() },
+        //blocos pequenos para um simbolo só atravessar varios blocos
+        Options { block_size: 1000, ..base },
+    ]
+}
+
+fn ida_e_volta(data: &[u8]) {
+    for options in todas_as_opcoes() {
+        let encoder = Encoder::new(options.clone());
+        let pipe = encoder.compress_bytes(data).unwrap();
+        assert_eq!(Decoder::new().decompress_bytes(&pipe).unwrap(), data, "{:?}", options);
+
+        let mut arquivo = Vec::new();
+        encoder.compress_seekable(Cursor::new(data), &mut arquivo).unwrap();
+        assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data, "{:?}", options);
+    }
+}
+
+fn cabecalho(original_len: u64) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&[VERSION, 0, 0]);
+    out.extend_from_slice(&original_len.to_be_bytes());
+    out
+}
+
+#[test]
+fn vazio_faz_ida_e_volta() {
+    ida_e_volta(b"");
+}
+
+#[test]
+fn um_simbolo_faz_ida_e_volta() {
+    ida_e_volta(b"a");
+    ida_e_volta(b"aaaa");
+    ida_e_volta("ééééé".as_bytes());
+    ida_e_volta(&[0u8; 3000]);
+    ida_e_volta(&vec![b'z'; 1 << 18]);
+}
+
+#[test]
+fn vazio_no_disco() {
+    let encoder = Encoder::default();
+    let mut esperado = cabecalho(0);
+    esperado.extend_from_slice(&[0, 0, 0, 0, 0]);
+    let mut arquivo = Vec::new();
+    encoder.compress_seekable(Cursor::new(b""), &mut arquivo).unwrap();
+    assert_eq!(arquivo, esperado);
+    assert_eq!(arquivo.len(), 20);
+
+    //de um pipe o tamanho não é conhecido e o modo de blocos liga o CRC por
+    //bloco (bit 3 das flags); o resto é igual
+    let mut esperado = cabecalho(UNKNOWN_LEN);
+    esperado[5] = 0b1000;
+    esperado.extend_from_slice(&[0, 0, 0, 0, 0]);
+    assert_eq!(encoder.compress_bytes(b"").unwrap(), esperado);
+}
+
+#[test]
+fn um_simbolo_no_disco() {
+    let mut arquivo = Vec::new();
+    Encoder::default().compress_seekable(Cursor::new(b"aaaa"), &mut arquivo).unwrap();
+
+    let mut esperado = cabecalho(4);
+    //bloco: 4 bytes, tabela com um simbolo ('a', tamanho 0), payload vazio
+    esperado.extend_from_slice(&[4, 1, b'a', 0, 0]);
+    //fim dos blocos e CRC
+    esperado.push(0);
+    esperado.extend_from_slice(&crc32(b"aaaa").to_be_bytes());
+    assert_eq!(arquivo, esperado);
+}
+
+#[test]
+fn um_simbolo_com_crc_errado() {
+    let mut arquivo = Vec::new();
+    Encoder::default().compress_seekable(Cursor::new(b"aaaa"), &mut arquivo).unwrap();
+    let ultimo = arquivo.len() - 1;
+    arquivo[ultimo] ^= 1;
+    match Decoder::new().decompress_bytes(&arquivo) {
+        Err(HuffmanError::ChecksumMismatch { .. }) => {}
+        outro => panic!("esperava ChecksumMismatch, veio {:?}", outro),
+    }
+}
+
+#[test]
+fn arvore_de_um_simbolo_e_vazia() {
+    assert!(Huffman::build_tree(&HashMap::new()).is_none());
+    assert!(limited_code_lengths(&HashMap::new(), 15).is_empty());
+
+    let freqs = HashMap::from([(b'a' as u32, 4)]);
+    let tree = Huffman::build_tree(&freqs).unwrap();
+    assert!(matches!(tree, Huffman::Leaf { sym: 97, freq: 4 }));
+    assert_eq!(tree.code_lengths(), vec![(97, 0)]);
+    assert_eq!(limited_code_lengths(&freqs, 15), vec![(97, 0)]);
+}
@@ -0,0 +1,56 @@
+//fixtures usadas por mais de um arquivo de teste. cada arquivo em tests/ é
+//um crate separado e usa só parte delas
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+//diretorio temporario só do teste `nome`, vazio
+pub fn temporario(nome: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("huffman-{}-{}", nome, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+//bytes pseudoaleatorios (xorshift): sem sequencias e sem relação com os
+//anteriores
+pub fn ruido(n: usize) -> Vec<u8> {
+    let mut x = 0x2545_f491_u32;
+    (0..n)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            (x >> 24) as u8
+        })
+        .collect()
+}
+
+//reader que entrega no maximo `passo` bytes por read, com um Interrupted de
+//vez em quando
+pub struct AosPoucos<'a> {
+    data: &'a [u8],
+    passo: usize,
+    chamadas: usize,
+}
+
+impl<'a> AosPoucos<'a> {
+    pub fn new(data: &'a [u8], passo: usize) -> Self {
+        AosPoucos { data, passo, chamadas: 0 }
+    }
+}
+
+impl Read for AosPoucos<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.chamadas += 1;
+        if self.chamadas.is_multiple_of(5) {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let n = buf.len().min(self.passo).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
@@ -0,0 +1,99 @@
+//Huffman de ordem 1 e 2: ganha da ordem 0 quando o byte anterior diz muito
+//sobre o proximo, e não perde quando não diz
+use huffman::context::{self, Tables};
+use huffman::{Decoder, Encoder, HuffmanError, Method, Options};
+
+mod common;
+use common::ruido;
+
+//palavras sorteadas de uma lista: depois de 'q' vem sempre 'u', e os dois
+//bytes anteriores quase sempre decidem o proximo
+fn texto() -> Vec<u8> {
+    let palavras = [
+        "que", "quando", "quatro", "bloco", "tabela", "contexto", "simbolo", "arquivo",
+        "frequencia", "codigo", "ordem", "entrada", "saida", "byte", "cabecalho", "payload",
+    ];
+    let mut x = 11u32;
+    let mut out = Vec::new();
+    for _ in 0..40_000 {
+        x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        out.extend_from_slice(palavras[(x >> 16) as usize % palavras.len()].as_bytes());
+        out.push(b' ');
+    }
+    out
+}
+
+fn compactar(data: &[u8], options: Options) -> Vec<u8> {
+    let arquivo = Encoder::new(options.clone()).compress_bytes(data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data, "{:?}", options);
+    arquivo
+}
+
+#[test]
+fn ganha_da_ordem_0_em_texto() {
+    let data = texto();
+    let ordem0 = compactar(&data, Options::default());
+    let ordem1 = compactar(&data, Options { method: Method::Context, ..Default::default() });
+    let ordem2 = compactar(
+        &data,
+        Options { method: Method::Context, context_order: 2, ..Default::default() },
+    );
+    assert!(ordem1.len() * 10 < ordem0.len() * 9, "{} {}", ordem1.len(), ordem0.len());
+    assert!(ordem2.len() < ordem1.len(), "{} {}", ordem2.len(), ordem1.len());
+}
+
+#[test]
+fn contextos_raros_usam_a_ordem_0() {
+    //sem estrutura: nenhum contexto paga a propria tabela
+    let data = ruido(50_000);
+    let tabelas = context::model(&data, 1, 15);
+    assert!(tabelas.contexts.is_empty());
+    let estatico = compactar(&data, Options::default());
+    for order in [1, 2] {
+        let options =
+            Options { method: Method::Context, context_order: order, ..Default::default() };
+        let arquivo = compactar(&data, options);
+        assert!(arquivo.len() <= estatico.len() + 2, "{} {}", arquivo.len(), estatico.len());
+    }
+
+    //só 'q' e 'u' ganham tabela; o resto cai na ordem 0
+    let mut data = data;
+    data.extend(b"qu".repeat(2_000));
+    let tabelas = context::model(&data, 1, 15);
+    let com_tabela: Vec<usize> = tabelas.contexts.iter().map(|(ctx, _)| *ctx).collect();
+    assert_eq!(com_tabela, [b'q' as usize, b'u' as usize]);
+    assert!(!tabelas.fallback.is_empty());
+    for options in [
+        Options { method: Method::Context, ..Default::default() },
+        Options {
+            method: Method::Context,
+            context_order: 2,
+            block_size: 999,
+            threads: 3,
+            ..Default::default()
+        },
+    ] {
+        compactar(&data, options);
+    }
+}
+
+#[test]
+fn tabelas_gravadas_fazem_ida_e_volta() {
+    let data = texto();
+    for order in [1, 2] {
+        let tabelas = context::model(&data, order, 12);
+        let mut buf = Vec::new();
+        context::write_tables(&tabelas, &mut buf);
+        assert_eq!(context::read_tables(&mut buf.as_slice()).unwrap(), tabelas);
+    }
+
+    //ordem que não existe e contexto além da ordem 1
+    let invalida = Tables { order: 3, fallback: Vec::new(), contexts: Vec::new() };
+    let mut buf = Vec::new();
+    context::write_tables(&invalida, &mut buf);
+    assert!(matches!(context::read_tables(&mut buf.as_slice()), Err(HuffmanError::Corrupt(_))));
+    let fora = Tables { order: 1, fallback: Vec::new(), contexts: vec![(300, vec![(1, 0)])] };
+    let mut buf = Vec::new();
+    context::write_tables(&fora, &mut buf);
+    assert!(context::read_tables(&mut buf.as_slice()).is_err());
+}
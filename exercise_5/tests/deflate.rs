@@ -0,0 +1,146 @@
+//gzip e zlib de verdade: os arquivos em tests/fixtures foram gravados pelo
+//gzip 1.12 (-1, -9 e dois membros concatenados) e pelo zlib do Python (nivel
+//9 e nivel 0, que só tem blocos stored), todos a partir de texto()
+use std::io::{self, Write};
+
+use huffman::deflate::{self, Wrapper};
+use huffman::{Decoder, Encoder, Options};
+
+mod common;
+use common::ruido;
+
+const GZIP_1: &[u8] = include_bytes!("fixtures/deflate_1.gz");
+const GZIP_9: &[u8] = include_bytes!("fixtures/deflate_9.gz");
+const GZIP_MEMBROS: &[u8] = include_bytes!("fixtures/deflate_membros.gz");
+const ZLIB_9: &[u8] = include_bytes!("fixtures/deflate_9.zz");
+const ZLIB_0: &[u8] = include_bytes!("fixtures/deflate_0.zz");
+
+//o texto que foi compactado nos fixtures
+fn texto() -> Vec<u8> {
+    (0..3000u32)
+        .flat_map(|i| {
+            format!(
+                "{:05} gzip e zlib: o mesmo DEFLATE com envelopes diferentes {}\n",
+                i,
+                i * 7919 % 1000
+            )
+            .into_bytes()
+        })
+        .collect()
+}
+
+//CRC-32, Adler-32 e tamanho de texto(), calculados pelo zlib
+const CRC_TEXTO: u32 = 0xDD9D_19CC;
+const ADLER_TEXTO: u32 = 0x3ABE_275F;
+const TAMANHO_TEXTO: usize = 191_670;
+
+fn inflar(arquivo: &[u8], wrapper: Wrapper) -> Vec<u8> {
+    assert_eq!(deflate::detect(arquivo), Some(wrapper));
+    let mut out = Vec::new();
+    let total = deflate::decompress(arquivo, &mut out, wrapper).unwrap();
+    assert_eq!(total, out.len() as u64);
+    //o Decoder reconhece o envelope sozinho
+    assert_eq!(Decoder::new().decompress_bytes(arquivo).unwrap(), out);
+    out
+}
+
+#[test]
+fn le_o_que_o_gzip_e_o_zlib_gravaram() {
+    let texto = texto();
+    assert_eq!(texto.len(), TAMANHO_TEXTO);
+    for arquivo in [GZIP_1, GZIP_9, GZIP_MEMBROS] {
+        assert_eq!(inflar(arquivo, Wrapper::Gzip), texto);
+    }
+    assert_eq!(inflar(ZLIB_9, Wrapper::Zlib), texto);
+    //blocos stored de 65535 bytes
+    assert_eq!(inflar(ZLIB_0, Wrapper::Zlib), &texto[..70_000]);
+
+    //o segundo membro começa logo depois do trailer do primeiro; cortado
+    //no meio ele tem que dar erro, não só o primeiro membro
+    let corte = GZIP_MEMBROS.len() - 100;
+    assert!(Decoder::new().decompress_bytes(&GZIP_MEMBROS[..corte]).is_err());
+    let mut crc_errado = GZIP_9.to_vec();
+    let n = crc_errado.len();
+    crc_errado[n - 8] ^= 1;
+    assert!(Decoder::new().decompress_bytes(&crc_errado).is_err());
+}
+
+fn compactar(data: &[u8], wrapper: Wrapper, level: u8) -> Vec<u8> {
+    let options = Options { wrapper: Some(wrapper), level, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+    arquivo
+}
+
+#[test]
+fn trailer_do_que_gravamos() {
+    let texto = texto();
+    for level in [0, 1, 6, 9] {
+        let gz = compactar(&texto, Wrapper::Gzip, level);
+        assert_eq!(gz[..3], [0x1f, 0x8b, 8]);
+        let trailer = &gz[gz.len() - 8..];
+        assert_eq!(trailer[..4], CRC_TEXTO.to_le_bytes(), "nivel {}", level);
+        assert_eq!(trailer[4..], (TAMANHO_TEXTO as u32).to_le_bytes());
+
+        let zz = compactar(&texto, Wrapper::Zlib, level);
+        assert_eq!(u16::from_be_bytes([zz[0], zz[1]]) % 31, 0);
+        assert_eq!(zz[zz.len() - 4..], ADLER_TEXTO.to_be_bytes(), "nivel {}", level);
+    }
+    //os valores de referencia das especificações
+    let gz = compactar(b"123456789", Wrapper::Gzip, 6);
+    assert_eq!(gz[gz.len() - 8..], [0x26, 0x39, 0xF4, 0xCB, 9, 0, 0, 0]);
+    let zz = compactar(b"Wikipedia", Wrapper::Zlib, 6);
+    assert_eq!(zz[zz.len() - 4..], 0x11E6_0398u32.to_be_bytes());
+    let vazio = compactar(b"", Wrapper::Zlib, 6);
+    assert_eq!(vazio[vazio.len() - 4..], [0, 0, 0, 1]);
+}
+
+//guarda o tamanho de cada write
+struct Escritas(Vec<usize>);
+
+impl Write for Escritas {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.push(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+//zlib só com blocos stored, montado à mão
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let pedacos: Vec<&[u8]> = data.chunks(65_535).collect();
+    for (i, pedaco) in pedacos.iter().enumerate() {
+        out.push((i + 1 == pedacos.len()) as u8);
+        let len = pedaco.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend_from_slice(pedaco);
+    }
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+#[test]
+fn blocos_stored_saem_aos_poucos() {
+    let data = ruido(4 << 20);
+    let zz = zlib_stored(&data);
+    assert_eq!(Decoder::new().decompress_bytes(&zz).unwrap(), data);
+
+    let mut escritas = Escritas(Vec::new());
+    let total = deflate::decompress(zz.as_slice(), &mut escritas, Wrapper::Zlib).unwrap();
+    assert_eq!(total, data.len() as u64);
+    assert_eq!(escritas.0.iter().sum::<usize>(), data.len());
+    //a saida não fica toda em memoria: sai em pedaços de poucas janelas
+    let maior = escritas.0.iter().max().copied().unwrap();
+    assert!(maior <= 3 * deflate::WINDOW + 65_535, "{}", maior);
+    assert!(escritas.0.len() > 16, "{}", escritas.0.len());
+}
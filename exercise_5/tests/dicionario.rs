@@ -0,0 +1,89 @@
+//dicionarios treinados: mensagens pequenas ficam menores que com a tabela
+//embutida, simbolos fora do treino passam pelo escape e o arquivo do
+//dicionario faz ida e volta com o mesmo id
+use std::sync::Arc;
+
+use huffman::{Alphabet, Decoder, Dictionary, Encoder, HuffmanError, Options};
+
+fn mensagem(i: u32) -> String {
+    format!(
+        "{{\"id\":{},\"user\":\"u{}\",\"event\":\"click\",\"ok\":{}}}",
+        i,
+        i * 7 % 1000,
+        i.is_multiple_of(3)
+    )
+}
+
+fn treinar(alphabet: Alphabet) -> Dictionary {
+    let amostras: Vec<String> = (0..200).map(mensagem).collect();
+    Dictionary::train(&amostras, alphabet, 15).unwrap()
+}
+
+fn com_dicionario(dict: &Dictionary) -> Encoder {
+    Encoder::new(Options { dictionary: Some(Arc::new(dict.clone())), ..Default::default() })
+}
+
+#[test]
+fn mensagens_pequenas_ficam_menores() {
+    let dict = treinar(Alphabet::Bytes);
+    let decoder = Decoder::new().with_dictionary(dict.clone());
+    for i in 1000..1020 {
+        let msg = mensagem(i);
+        let arquivo = com_dicionario(&dict).compress_bytes(msg.as_bytes()).unwrap();
+        let sem = Encoder::default().compress_bytes(msg.as_bytes()).unwrap();
+        assert!(arquivo.len() < sem.len(), "{} {}", arquivo.len(), sem.len());
+        assert_eq!(decoder.decompress_bytes(&arquivo).unwrap(), msg.as_bytes());
+    }
+}
+
+#[test]
+fn simbolos_fora_do_treino_usam_escape() {
+    let dict = treinar(Alphabet::Bytes);
+    let data: Vec<u8> = (0..=255).chain(b"{\"id\":1}".iter().copied()).collect();
+    let arquivo = com_dicionario(&dict).compress_bytes(&data).unwrap();
+    let decoder = Decoder::new().with_dictionary(dict);
+    assert_eq!(decoder.decompress_bytes(&arquivo).unwrap(), data);
+
+    let dict = treinar(Alphabet::Chars);
+    let texto = "{\"user\":\"ação 🦀\"}";
+    let arquivo = com_dicionario(&dict).compress_bytes(texto.as_bytes()).unwrap();
+    let decoder = Decoder::new().with_dictionary(dict);
+    assert_eq!(decoder.decompress_bytes(&arquivo).unwrap(), texto.as_bytes());
+}
+
+#[test]
+fn sem_o_dicionario_nao_descompacta() {
+    let dict = treinar(Alphabet::Bytes);
+    let arquivo = com_dicionario(&dict).compress_bytes(mensagem(1).as_bytes()).unwrap();
+    assert!(matches!(
+        Decoder::new().decompress_bytes(&arquivo),
+        Err(HuffmanError::MissingDictionary(id)) if id == dict.id()
+    ));
+    //outro dicionario, com outro id
+    let outro = Dictionary::train(["abc"], Alphabet::Bytes, 15).unwrap();
+    assert_ne!(outro.id(), dict.id());
+    assert!(matches!(
+        Decoder::new().with_dictionary(outro).decompress_bytes(&arquivo),
+        Err(HuffmanError::MissingDictionary(_))
+    ));
+}
+
+#[test]
+fn arquivo_do_dicionario_faz_ida_e_volta() {
+    let dict = treinar(Alphabet::Chars);
+    let mut buf = Vec::new();
+    dict.write(&mut buf).unwrap();
+    let lido = Dictionary::read(buf.as_slice()).unwrap();
+    assert_eq!(lido.id(), dict.id());
+    assert_eq!(lido.alphabet(), Alphabet::Chars);
+    assert_eq!(lido.lengths(), dict.lengths());
+    //treinar de novo com as mesmas amostras dá o mesmo id
+    assert_eq!(treinar(Alphabet::Chars).id(), dict.id());
+
+    let n = buf.len();
+    buf[n - 1] ^= 1;
+    assert!(matches!(
+        Dictionary::read(buf.as_slice()),
+        Err(HuffmanError::ChecksumMismatch { .. })
+    ));
+}
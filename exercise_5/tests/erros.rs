@@ -0,0 +1,105 @@
+//cada tipo de arquivo ruim tem o seu HuffmanError: quem chama precisa
+//distinguir "não é um .huff" de "versão nova demais" de "arquivo cortado"
+use huffman::container::{HEADER_LEN, MAGIC};
+use huffman::huffman::{read_varint, write_varint};
+use huffman::{Alphabet, Decoder, Encoder, HuffmanError, Options};
+
+fn arquivo() -> Vec<u8> {
+    Encoder::default().compress_bytes(&b"erro por erro, bloco por bloco\n".repeat(50)).unwrap()
+}
+
+fn ler(data: &[u8]) -> Result<Vec<u8>, HuffmanError> {
+    Decoder::new().decompress_bytes(data)
+}
+
+//o erro, sem o BadBlock que diz em qual bloco ele aconteceu
+fn causa(e: HuffmanError) -> HuffmanError {
+    match e {
+        HuffmanError::BadBlock { error, .. } => *error,
+        e => e,
+    }
+}
+
+#[test]
+fn magic_versao_e_flags() {
+    assert!(matches!(ler(b"isto nao e um arquivo .huff"), Err(HuffmanError::UnknownFormat)));
+    assert!(matches!(ler(b""), Err(HuffmanError::UnknownFormat)));
+
+    let bom = arquivo();
+    for versao in [0, 3, 0xFF] {
+        let mut errado = bom.clone();
+        errado[MAGIC.len()] = versao;
+        match ler(&errado) {
+            Err(HuffmanError::UnsupportedVersion(v)) => assert_eq!(v, versao),
+            outro => panic!("versão {}: {:?}", versao, outro),
+        }
+    }
+    for flag in [0x20, 0x40, 0x80] {
+        let mut errado = bom.clone();
+        errado[MAGIC.len() + 1] |= flag;
+        match ler(&errado) {
+            Err(HuffmanError::UnknownFlags(f)) => assert_eq!(f & flag, flag),
+            outro => panic!("flag {:#x}: {:?}", flag, outro),
+        }
+    }
+}
+
+#[test]
+fn arquivo_cortado() {
+    let bom = arquivo();
+    //dentro do cabeçalho
+    for n in [MAGIC.len(), MAGIC.len() + 1, HEADER_LEN - 1] {
+        assert!(matches!(ler(&bom[..n]), Err(HuffmanError::TruncatedHeader)), "{}", n);
+    }
+    //dentro do bloco e no trailer
+    for n in [HEADER_LEN + 1, bom.len() / 2, bom.len() - 1] {
+        match ler(&bom[..n]).map_err(causa) {
+            Err(HuffmanError::Truncated) => {}
+            outro => panic!("{} bytes: {:?}", n, outro),
+        }
+    }
+}
+
+#[test]
+fn tamanho_crc_e_utf8() {
+    let bom = arquivo();
+    let mut tamanho = bom.clone();
+    tamanho[HEADER_LEN - 1] ^= 1;
+    assert!(matches!(ler(&tamanho), Err(HuffmanError::LengthMismatch { .. })));
+    let mut crc = bom.clone();
+    let n = crc.len();
+    crc[n - 1] ^= 1;
+    assert!(matches!(ler(&crc), Err(HuffmanError::ChecksumMismatch { .. })));
+
+    let chars = Encoder::new(Options { alphabet: Alphabet::Chars, ..Default::default() });
+    assert!(matches!(chars.compress_bytes(b"\xff\xfe"), Err(HuffmanError::InvalidUtf8)));
+}
+
+#[test]
+fn varint_de_dez_bytes() {
+    for v in [0, 127, 128, u32::MAX as u64, 1 << 63, u64::MAX] {
+        let mut buf = Vec::new();
+        write_varint(v, &mut buf);
+        assert_eq!(read_varint(&mut buf.as_slice()).unwrap(), v);
+    }
+    //no 10o byte só cabe o bit 63: qualquer coisa acima seria perdida
+    let mut maximo = vec![0xFF; 9];
+    maximo.push(0x01);
+    assert_eq!(read_varint(&mut maximo.as_slice()).unwrap(), u64::MAX);
+    for ultimo in [0x02, 0x7F, 0x81] {
+        let mut longo = vec![0xFF; 9];
+        longo.push(ultimo);
+        longo.push(0);
+        assert!(
+            matches!(read_varint(&mut longo.as_slice()), Err(HuffmanError::Corrupt(_))),
+            "{:#x}",
+            ultimo
+        );
+    }
+    //onze bytes, mesmo que todos zerados
+    let mut onze = vec![0x80; 10];
+    onze.push(0);
+    assert!(matches!(read_varint(&mut onze.as_slice()), Err(HuffmanError::Corrupt(_))));
+    //acaba no meio
+    assert!(matches!(read_varint(&mut [0x80u8, 0x80].as_slice()), Err(HuffmanError::Truncated)));
+}
@@ -0,0 +1,42 @@
+//compactação de fluxo: os dois modos têm que devolver a entrada, inclusive
+//com chars de varios bytes cortados na fronteira de um bloco, e os blocos
+//codificados em threads saem na ordem da entrada
+use std::io::Cursor;
+
+use huffman::{Alphabet, Decoder, Encoder, Options};
+
+fn ida_e_volta(data: &[u8], options: Options) {
+    let encoder = Encoder::new(options);
+    let blocos = encoder.compress_bytes(data).unwrap();
+    let mut unico = Vec::new();
+    encoder.compress_seekable(Cursor::new(data), &mut unico).unwrap();
+    for arquivo in [blocos, unico] {
+        assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+    }
+}
+
+#[test]
+fn blocos_pequenos_e_chars_cortados() {
+    let texto = "ação, pão e café ☕ — 𝄞 ".repeat(500);
+    for block_size in [4, 5, 7, 64, 1 << 16] {
+        for alphabet in [Alphabet::Bytes, Alphabet::Chars] {
+            ida_e_volta(texto.as_bytes(), Options { alphabet, block_size, ..Default::default() });
+        }
+    }
+    ida_e_volta(b"", Options::default());
+    ida_e_volta(b"a", Options::default());
+}
+
+#[test]
+fn mesmo_arquivo_com_qualquer_numero_de_threads() {
+    let texto: Vec<u8> = (0..200_000u64).map(|i| (i * i % 251) as u8).collect();
+    let compactar = |threads| {
+        let options = Options { block_size: 10_000, threads, ..Default::default() };
+        Encoder::new(options).compress_bytes(&texto).unwrap()
+    };
+    let sequencial = compactar(1);
+    for threads in [2, 3, 8, 64] {
+        assert_eq!(compactar(threads), sequencial, "{} threads", threads);
+    }
+    assert_eq!(Decoder::new().decompress_bytes(&sequencial).unwrap(), texto);
+}
@@ -0,0 +1,96 @@
+//indice de busca: read_at tem que devolver o mesmo trecho que a
+//descompactação inteira, e o arquivo com indice continua legivel do começo
+use std::io::Cursor;
+
+use huffman::container::{self, SeekIndex};
+use huffman::{Alphabet, Decoder, Encoder, HuffmanError, Method, Options};
+
+fn dados() -> Vec<u8> {
+    (0..40_000u32).flat_map(|i| format!("linha {} do log\n", i * 7 % 1000).into_bytes()).collect()
+}
+
+#[test]
+fn read_at_confere_com_o_original() {
+    let data = dados();
+    for options in [
+        Options { block_size: 10_000, seek_index: true, ..Default::default() },
+        Options { method: Method::Lz77, block_size: 4096, seek_index: true, ..Default::default() },
+        Options { alphabet: Alphabet::Chars, block_size: 999, seek_index: true, ..Default::default() },
+    ] {
+        let arquivo = Encoder::new(options.clone()).compress_bytes(&data).unwrap();
+        let decoder = Decoder::new();
+        assert_eq!(decoder.decompress_bytes(&arquivo).unwrap(), data, "{:?}", options);
+
+        let n = data.len() as u64;
+        for (offset, len) in [(0, 10), (9_999, 2), (10_000, 25_000), (n - 5, 100), (n, 10), (123, 0)] {
+            let trecho = decoder.read_at(Cursor::new(&arquivo), offset, len).unwrap();
+            let de = (offset as usize).min(data.len());
+            let ate = (de + len).min(data.len());
+            assert_eq!(trecho, &data[de..ate], "{:?} {} {}", options, offset, len);
+        }
+    }
+}
+
+#[test]
+fn vazio_com_indice() {
+    let options = Options { seek_index: true, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(b"").unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), b"");
+    assert_eq!(Decoder::new().read_at(Cursor::new(&arquivo), 0, 10).unwrap(), b"");
+}
+
+#[test]
+fn sem_indice() {
+    let arquivo = Encoder::default().compress_bytes(&dados()).unwrap();
+    assert!(matches!(
+        Decoder::new().read_at(Cursor::new(&arquivo), 0, 10),
+        Err(HuffmanError::NoIndex)
+    ));
+}
+
+//o mesmo arquivo com outros pontos no indice
+fn trocar_indice(arquivo: &[u8], pontos: &[(u64, u64)]) -> Vec<u8> {
+    let index = SeekIndex::load(&mut Cursor::new(arquivo)).unwrap();
+    let inicio = index.points.last().unwrap().0 + 5;
+    let mut out = arquivo[..inicio as usize].to_vec();
+    container::write_index(&mut out, pontos, inicio).unwrap();
+    out
+}
+
+#[test]
+fn indice_adulterado_vira_erro() {
+    let data = dados();
+    let options = Options { block_size: 10_000, seek_index: true, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(&data).unwrap();
+    let pontos = SeekIndex::load(&mut Cursor::new(&arquivo)).unwrap().points;
+    assert!(pontos.len() > 4);
+
+    let mut primeiro = pontos.clone();
+    primeiro[0].1 = 5;
+    let mut repetido = pontos.clone();
+    repetido[2] = repetido[1];
+    let mut voltando = pontos.clone();
+    voltando[2].1 = voltando[1].1;
+    for pontos in [primeiro, repetido, voltando] {
+        let errado = trocar_indice(&arquivo, &pontos);
+        assert!(matches!(
+            SeekIndex::load(&mut Cursor::new(&errado)),
+            Err(HuffmanError::Corrupt(_))
+        ));
+        assert!(Decoder::new().read_at(Cursor::new(&errado), 0, 10).is_err());
+    }
+
+    //em ordem, mas o bloco 1 não tem o tamanho que o indice diz
+    for delta in [-3i64, 3] {
+        let mut pontos = pontos.clone();
+        pontos[2].1 = pontos[2].1.checked_add_signed(delta).unwrap();
+        let errado = trocar_indice(&arquivo, &pontos);
+        assert!(SeekIndex::load(&mut Cursor::new(&errado)).is_ok());
+        for offset in [pontos[1].1, pontos[2].1 - 1, pontos[2].1] {
+            let lido = Decoder::new().read_at(Cursor::new(&errado), offset, 20_000);
+            assert!(lido.is_err(), "{} {}", delta, offset);
+        }
+        //o bloco 0 continua certo
+        assert_eq!(Decoder::new().read_at(Cursor::new(&errado), 0, 100).unwrap(), &data[..100]);
+    }
+}
@@ -0,0 +1,58 @@
+//CRC por bloco: um byte estragado no meio do arquivo tem que virar um erro
+//que diz qual bloco foi, em todos os métodos do modo de blocos
+use std::io::Cursor;
+
+use huffman::container::SeekIndex;
+use huffman::{info, Alphabet, Coder, Decoder, Encoder, HuffmanError, Method, Options};
+
+fn dados() -> Vec<u8> {
+    (0..6_000u32).flat_map(|i| format!("registro {} ok\n", i * 13 % 997).into_bytes()).collect()
+}
+
+#[test]
+fn aponta_o_bloco_estragado() {
+    let data = dados();
+    let base = Options { block_size: 10_000, seek_index: true, ..Default::default() };
+    for options in [
+        base.clone(),
+        Options { alphabet: Alphabet::Chars, ..base.clone() },
+        Options { coder: Coder::Rans, ..base.clone() },
+        Options { rle: true, ..base.clone() },
+        Options { method: Method::Lz77, ..base.clone() },
+        Options { method: Method::Bwt, ..base.clone() },
+        Options { method: Method::Context, ..base },
+    ] {
+        let mut arquivo = Encoder::new(options.clone()).compress_bytes(&data).unwrap();
+        //o indice diz onde o bloco 3 começa; o byte do meio dele é payload
+        let index = SeekIndex::load(&mut Cursor::new(&arquivo)).unwrap();
+        let (inicio, original) = index.points[3];
+        let fim = index.points[4].0;
+        arquivo[((inicio + fim) / 2) as usize] ^= 0x10;
+        match Decoder::new().decompress_bytes(&arquivo) {
+            Err(HuffmanError::BadBlock { block: 3, offset, .. }) => assert_eq!(offset, original),
+            outro => panic!("{:?}: esperava BadBlock no bloco 3, veio {:?}", options, outro),
+        }
+        assert!(matches!(
+            Decoder::new().read_at(Cursor::new(&arquivo), original, 10),
+            Err(HuffmanError::BadBlock { block: 3, .. })
+        ));
+        //os blocos antes dele continuam legiveis pelo indice
+        let trecho = Decoder::new().read_at(Cursor::new(&arquivo), 0, 100).unwrap();
+        assert_eq!(trecho, &data[..100]);
+    }
+}
+
+#[test]
+fn sem_crc_por_bloco() {
+    let data = dados();
+    let com = Options { block_size: 10_000, ..Default::default() };
+    let sem = Options { block_checksums: false, ..com.clone() };
+    let com = Encoder::new(com).compress_bytes(&data).unwrap();
+    let sem = Encoder::new(sem).compress_bytes(&data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&sem).unwrap(), data);
+
+    let resumo = info(com.as_slice()).unwrap();
+    assert_eq!(resumo.original_len, Some(data.len() as u64));
+    assert!(resumo.header.unwrap().block_crc);
+    assert_eq!(com.len(), sem.len() + 4 * resumo.blocks as usize);
+}
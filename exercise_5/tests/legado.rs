@@ -0,0 +1,58 @@
+//formatos das versões antigas: os arquivos em tests/fixtures foram gravados
+//pelo main.rs e pelo compactador.rs de antes do .huff. sem assinatura, só o
+//tamanho exato diz qual é o formato, então um byte a mais ou a menos tem que
+//ser recusado em vez de virar lixo
+use huffman::container::{detect, Format};
+use huffman::legacy;
+use huffman::{Decoder, HuffmanError};
+
+const ARVORE: &[u8] = include_bytes!("fixtures/arvore_main.bin");
+const ARVORE_TEXTO: &[u8] = include_bytes!("fixtures/arvore_main.txt");
+const TABELA: &[u8] = include_bytes!("fixtures/tabela_compactador.huff");
+const TABELA_TEXTO: &[u8] = include_bytes!("fixtures/tabela_compactador.txt");
+
+#[test]
+fn arvore_do_main_antigo() {
+    assert_eq!(detect(ARVORE), Some(Format::LegacyTree));
+    assert!(!legacy::parece_tabela(ARVORE));
+    assert_eq!(legacy::ler_arvore(ARVORE).unwrap(), ARVORE_TEXTO);
+    assert_eq!(Decoder::new().decompress_bytes(ARVORE).unwrap(), ARVORE_TEXTO);
+}
+
+#[test]
+fn tabela_do_compactador_antigo() {
+    //texto com acentos: as chaves da tabela são code points
+    assert!(std::str::from_utf8(TABELA_TEXTO).unwrap().chars().any(|c| c.len_utf8() > 1));
+    assert_eq!(detect(TABELA), Some(Format::LegacyFreqTable));
+    assert!(!legacy::parece_arvore(TABELA));
+    assert_eq!(legacy::ler_tabela(TABELA).unwrap(), TABELA_TEXTO);
+    assert_eq!(Decoder::new().decompress_bytes(TABELA).unwrap(), TABELA_TEXTO);
+}
+
+#[test]
+fn tamanho_errado_e_recusado() {
+    for arquivo in [ARVORE, TABELA] {
+        let mut maior = arquivo.to_vec();
+        maior.push(0);
+        let menor = &arquivo[..arquivo.len() - 1];
+        for errado in [&maior[..], menor] {
+            assert_eq!(detect(errado), None, "{} bytes", errado.len());
+            assert!(matches!(
+                Decoder::new().decompress_bytes(errado),
+                Err(HuffmanError::UnknownFormat)
+            ));
+        }
+        assert!(matches!(legacy::ler_arvore(menor), Err(HuffmanError::UnknownFormat)));
+        assert!(matches!(legacy::ler_tabela(menor), Err(HuffmanError::UnknownFormat)));
+    }
+
+    //cabeçalho da arvore dizendo um bit a mais do que o payload tem
+    let mut bits_demais = ARVORE.to_vec();
+    let bits = u32::from_be_bytes(ARVORE[4..8].try_into().unwrap());
+    bits_demais[4..8].copy_from_slice(&(bits.next_multiple_of(8) + 1).to_be_bytes());
+    assert_eq!(detect(&bits_demais), None);
+    //tabela cujo total de bytes do texto não bate com as frequencias
+    let mut texto_maior = TABELA.to_vec();
+    texto_maior[5] += 1;
+    assert_eq!(detect(&texto_maior), None);
+}
@@ -0,0 +1,72 @@
+//package-merge: frequencias de Fibonacci dão a arvore mais desequilibrada
+//possivel, com um código a mais por simbolo, e o limite tem que segurar
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use huffman::container::{self, MAGIC};
+use huffman::huffman::{limited_code_lengths, Huffman, DEFAULT_MAX_CODE_LEN};
+use huffman::{Decoder, Encoder, Options};
+
+fn fibonacci(n: usize) -> Vec<u64> {
+    let mut f = vec![1u64, 1];
+    while f.len() < n {
+        f.push(f[f.len() - 1] + f[f.len() - 2]);
+    }
+    f
+}
+
+//soma de Kraft em unidades de 2^-64
+fn kraft(lengths: &[(u32, u8)]) -> u128 {
+    lengths.iter().map(|&(_, len)| 1u128 << (64 - len)).sum()
+}
+
+#[test]
+fn sessenta_simbolos_de_fibonacci() {
+    let freqs: HashMap<u32, u64> = (0..).zip(fibonacci(60)).collect();
+    //sem limite o simbolo mais raro ficaria com 59 bits
+    let livre = Huffman::build_tree(&freqs).unwrap().code_lengths();
+    assert!(livre.iter().any(|&(_, len)| len > 32));
+
+    for max_len in [DEFAULT_MAX_CODE_LEN, 12, 8, 6] {
+        let lengths = limited_code_lengths(&freqs, max_len);
+        assert_eq!(lengths.len(), 60);
+        assert!(lengths.iter().all(|&(_, len)| len >= 1 && len <= max_len), "{}", max_len);
+        assert!(kraft(&lengths) <= 1 << 64, "{}", max_len);
+        //os mais frequentes nunca ficam com código maior
+        let tamanho: HashMap<u32, u8> = lengths.into_iter().collect();
+        assert!((1..60).all(|s| tamanho[&s] <= tamanho[&(s - 1)]), "{}", max_len);
+    }
+    //limite menor que o alfabeto pede: sobe para o minimo, 6 bits
+    let lengths = limited_code_lengths(&freqs, 3);
+    assert!(lengths.iter().all(|&(_, len)| len <= 6));
+    assert_eq!(kraft(&lengths), 1 << 64);
+}
+
+#[test]
+fn arquivo_respeita_max_bits() {
+    //26 simbolos com contagens de Fibonacci (~320 mil bytes): 25 bits sem limite
+    let mut data = Vec::new();
+    for (i, f) in fibonacci(26).into_iter().enumerate() {
+        data.extend(std::iter::repeat_n(b'a' + i as u8, f as usize));
+    }
+    //embaralha para não ficar em sequencias
+    let mut x = 7u64;
+    for i in (1..data.len()).rev() {
+        x = x.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+        data.swap(i, (x >> 33) as usize % (i + 1));
+    }
+
+    for max_code_len in [DEFAULT_MAX_CODE_LEN, 10, 5] {
+        let options = Options { max_code_len, block_size: 1 << 20, ..Default::default() };
+        let arquivo = Encoder::new(options).compress_bytes(&data).unwrap();
+        assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+
+        let mut input = Cursor::new(&arquivo);
+        input.set_position(MAGIC.len() as u64);
+        let header = container::read_header(&mut input).unwrap();
+        let block = container::read_block_header(&mut input, &header).unwrap().unwrap();
+        assert_eq!(block.lengths.len(), 26);
+        assert!(block.lengths.iter().all(|&(_, len)| len <= max_code_len), "{}", max_code_len);
+        assert!(kraft(&block.lengths) <= 1 << 64);
+    }
+}
@@ -0,0 +1,106 @@
+//LZ77: referencias que se sobrepõem ao que estão copiando (distancia menor
+//que o comprimento) e distancias na borda da janela
+use huffman::lz77::{
+    self, distance_base, distance_code, length_base, length_code, Token, MAX_MATCH, MAX_WINDOW,
+};
+use huffman::{Decoder, Encoder, Method, Options};
+
+mod common;
+use common::ruido;
+
+//desfaz os tokens copiando byte a byte, como o descompactador
+fn expandir(tokens: &[Token]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::new();
+    for &t in tokens {
+        match t {
+            Token::Literal(b) => out.push(b),
+            Token::Match { len, dist } => {
+                assert!(dist as usize <= out.len(), "distancia {} antes do inicio", dist);
+                for _ in 0..len {
+                    out.push(out[out.len() - dist as usize]);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn ida_e_volta(data: &[u8], window: usize, level: u8) {
+    let options = Options { method: Method::Lz77, window, level, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+}
+
+#[test]
+fn referencias_sobrepostas() {
+    //um byte repetido: uma referencia de distancia 1 copia o que ela mesma
+    //acabou de escrever
+    let mut data = vec![b'x'; 1_000];
+    data.extend(b"abc".repeat(400));
+    data.extend(ruido(50));
+    data.extend(b"abcd".repeat(100));
+    for level in [1, 6, 9] {
+        let tokens = lz77::tokenize(&data, lz77::DEFAULT_WINDOW, level);
+        assert_eq!(expandir(&tokens), data);
+        let matches: Vec<(u16, u32)> = tokens
+            .iter()
+            .filter_map(|t| match *t {
+                Token::Match { len, dist } => Some((len, dist)),
+                Token::Literal(_) => None,
+            })
+            .collect();
+        assert!(matches.contains(&(MAX_MATCH as u16, 1)), "{:?}", &matches[..4]);
+        assert!(matches.iter().any(|&(len, dist)| dist == 3 && len as usize == MAX_MATCH));
+        assert!(matches.iter().all(|&(len, _)| (3..=MAX_MATCH as u16).contains(&len)));
+        //fora os 50 bytes de ruido, quase tudo vira referencia
+        assert!(tokens.len() < 50 + 30, "{}", tokens.len());
+        ida_e_volta(&data, lz77::DEFAULT_WINDOW, level);
+    }
+}
+
+#[test]
+fn distancia_na_borda_da_janela() {
+    let window = 1_024;
+    let trecho = ruido(64);
+    for (folga, alcanca) in [(0, true), (1, false)] {
+        //o trecho se repete exatamente `window` (ou window + 1) bytes depois
+        let mut data = trecho.clone();
+        data.extend(ruido(window + folga + 64).split_off(64 + 64));
+        data.extend(&trecho);
+        assert_eq!(data.len(), window + folga + 64);
+
+        let tokens = lz77::tokenize(&data, window, 9);
+        assert_eq!(expandir(&tokens), data);
+        let distancias: Vec<u32> = tokens
+            .iter()
+            .filter_map(|t| match *t {
+                Token::Match { dist, .. } => Some(dist),
+                Token::Literal(_) => None,
+            })
+            .collect();
+        assert!(distancias.iter().all(|&d| d as usize <= window), "{:?}", distancias);
+        assert_eq!(distancias.contains(&(window as u32)), alcanca, "folga {}", folga);
+        ida_e_volta(&data, window, 9);
+    }
+}
+
+#[test]
+fn codigos_de_comprimento_e_distancia() {
+    for len in 3..=MAX_MATCH {
+        let (sym, extra, valor) = length_code(len);
+        let (base, bits) = length_base(sym).unwrap();
+        assert_eq!((bits, base + valor as usize), (extra, len));
+    }
+    let mut bordas = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 32 * 1024, MAX_WINDOW];
+    for k in 3..24 {
+        bordas.extend([(1 << k) - 1, 1 << k, (1 << k) + 1]);
+    }
+    for dist in bordas {
+        let (sym, extra, valor) = distance_code(dist);
+        assert!(sym <= lz77::MAX_DIST_SYMBOL, "{}", dist);
+        let (base, bits) = distance_base(sym).unwrap();
+        assert_eq!((bits, base + valor as usize), (extra, dist), "{}", dist);
+    }
+    assert_eq!(length_base(286), None);
+    assert_eq!(distance_base(lz77::MAX_DIST_SYMBOL + 1), None);
+}
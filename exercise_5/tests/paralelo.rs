@@ -0,0 +1,98 @@
+//descompactação paralela de arquivo para arquivo: com o indice cada thread
+//escreve os seus blocos na posição certa, e o resultado (e os erros) é o
+//mesmo do caminho em sequencia
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::Path;
+
+use huffman::container::SeekIndex;
+use huffman::crc32::{combine, crc32};
+use huffman::{Alphabet, Decoder, Encoder, HuffmanError, Method, Options, Wrapper};
+
+mod common;
+use common::temporario;
+
+fn dados() -> Vec<u8> {
+    (0..20_000u32)
+        .flat_map(|i| format!("linha {} de {}\n", i, i * 31 % 1009).into_bytes())
+        .collect()
+}
+
+//descompacta `arquivo` de um arquivo para outro com `threads` threads
+fn descompactar(dir: &Path, arquivo: &[u8], threads: usize) -> Result<Vec<u8>, HuffmanError> {
+    let (entrada, saida) = (dir.join("entrada.huff"), dir.join("saida"));
+    fs::write(&entrada, arquivo).unwrap();
+    //lixo maior que o resultado: a saida precisa ficar com o tamanho certo
+    fs::write(&saida, vec![0xAA; 600_000]).unwrap();
+    let output = File::options().write(true).open(&saida).unwrap();
+    let total = Decoder::with_threads(threads).decompress_file(&File::open(&entrada)?, &output)?;
+    let out = fs::read(&saida).unwrap();
+    assert_eq!(total, out.len() as u64);
+    Ok(out)
+}
+
+#[test]
+fn blocos_em_paralelo_na_posicao_certa() {
+    let dir = temporario("paralelo");
+    let data = dados();
+    let base = Options { block_size: 7_000, seek_index: true, threads: 3, ..Default::default() };
+    for options in [
+        base.clone(),
+        Options { alphabet: Alphabet::Chars, ..base.clone() },
+        Options { method: Method::Lz77, ..base.clone() },
+        Options { method: Method::Context, context_order: 2, ..base.clone() },
+        Options { rle: true, block_checksums: false, ..base.clone() },
+        //sem indice e gzip vão pelo caminho em sequencia
+        Options { seek_index: false, ..base.clone() },
+        Options { wrapper: Some(Wrapper::Gzip), ..base },
+    ] {
+        let arquivo = Encoder::new(options.clone()).compress_bytes(&data).unwrap();
+        for threads in [1, 4, 64] {
+            assert_eq!(descompactar(&dir, &arquivo, threads).unwrap(), data, "{:?}", options);
+        }
+    }
+    let vazio = Encoder::new(Options { seek_index: true, ..Default::default() })
+        .compress_bytes(b"")
+        .unwrap();
+    assert!(descompactar(&dir, &vazio, 4).unwrap().is_empty());
+}
+
+#[test]
+fn erro_do_primeiro_bloco_estragado() {
+    let dir = temporario("paralelo-erro");
+    let data = dados();
+    let options = Options { block_size: 7_000, seek_index: true, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(&data).unwrap();
+    let index = SeekIndex::load(&mut Cursor::new(&arquivo)).unwrap();
+    let meio = |i: usize| ((index.points[i].0 + index.points[i + 1].0) / 2) as usize;
+
+    //os blocos 3 e 6 caem em threads diferentes; vale o 3
+    let mut estragado = arquivo.clone();
+    estragado[meio(3)] ^= 0x10;
+    estragado[meio(6)] ^= 0x10;
+    for threads in [1, 4] {
+        match descompactar(&dir, &estragado, threads) {
+            Err(HuffmanError::BadBlock { block: 3, offset, .. }) => {
+                assert_eq!(offset, index.points[3].1)
+            }
+            outro => panic!("{} threads: esperava BadBlock no bloco 3, veio {:?}", threads, outro),
+        }
+    }
+
+    //CRC do arquivo inteiro, conferido a partir dos CRCs dos blocos
+    let mut trailer = arquivo.clone();
+    trailer[index.points.last().unwrap().0 as usize + 2] ^= 1;
+    assert!(matches!(
+        descompactar(&dir, &trailer, 4),
+        Err(HuffmanError::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn crc_combinado() {
+    let data = dados();
+    for corte in [0, 1, 8, 1000, data.len() - 1, data.len()] {
+        let (a, b) = data.split_at(corte);
+        assert_eq!(combine(crc32(a), crc32(b), b.len() as u64), crc32(&data), "{}", corte);
+    }
+}
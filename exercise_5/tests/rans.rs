@@ -0,0 +1,47 @@
+//rANS: ida e volta, ganho sobre o Huffman com um simbolo dominante e
+//tabelas normalizadas que somam 2^SCALE_BITS
+use std::collections::HashMap;
+
+use huffman::rans::{normalize, SCALE_BITS};
+use huffman::{Alphabet, Coder, Decoder, Encoder, Options};
+
+//90% zeros, o resto espalhado em poucos valores
+fn telemetria() -> Vec<u8> {
+    let mut x = 12345u32;
+    (0..300_000)
+        .map(|_| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let r = (x >> 16) % 100;
+            if r < 90 { 0 } else { r as u8 }
+        })
+        .collect()
+}
+
+#[test]
+fn ganha_do_huffman_com_simbolo_dominante() {
+    let data = telemetria();
+    let huffman = Encoder::default().compress_bytes(&data).unwrap();
+    let options = Options { coder: Coder::Rans, block_size: 100_000, ..Default::default() };
+    let rans = Encoder::new(options).compress_bytes(&data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&rans).unwrap(), data);
+    //o Huffman gasta pelo menos 1 bit por simbolo
+    assert!(huffman.len() as f64 > data.len() as f64 / 8.0);
+    assert!((rans.len() as f64) < huffman.len() as f64 * 0.75, "{} {}", rans.len(), huffman.len());
+}
+
+#[test]
+fn chars_faz_ida_e_volta() {
+    let texto = "ação, coração e um 🦀 de vez em quando\n".repeat(500);
+    let options = Options { coder: Coder::Rans, alphabet: Alphabet::Chars, ..Default::default() };
+    let arquivo = Encoder::new(options).compress_bytes(texto.as_bytes()).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), texto.as_bytes());
+}
+
+#[test]
+fn tabela_soma_o_total() {
+    let freqs = HashMap::from([(0, 1_000_000_000), (1, 1), (2, 3), (7, 500)]);
+    let tabela = normalize(&freqs);
+    assert_eq!(tabela.iter().map(|&(_, f)| f as u64).sum::<u64>(), 1 << SCALE_BITS);
+    assert!(tabela.iter().all(|&(_, f)| f >= 1));
+    assert_eq!(tabela.iter().map(|&(s, _)| s).collect::<Vec<_>>(), vec![0, 1, 2, 7]);
+}
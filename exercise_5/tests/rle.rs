@@ -0,0 +1,100 @@
+//RLE antes do Huffman estático: sequencias longas ficam abaixo de 1 bit por
+//simbolo, e os blocos em que não ajuda continuam sem ele
+use std::io::Cursor;
+
+use huffman::container::{self, MAGIC};
+use huffman::rle::{runs, Piece};
+use huffman::{Alphabet, Decoder, Encoder, Options};
+
+mod common;
+use common::ruido;
+
+fn com_rle() -> Options {
+    Options { rle: true, ..Default::default() }
+}
+
+fn bitmap() -> Vec<u8> {
+    let mut data = vec![0u8; 100_000];
+    for i in (0..data.len()).step_by(997) {
+        data[i..(i + 40).min(100_000)].fill(0xFF);
+    }
+    data
+}
+
+//o byte de RLE de cada bloco do arquivo
+fn marcadores(arquivo: &[u8]) -> Vec<bool> {
+    let mut input = Cursor::new(arquivo);
+    input.set_position(MAGIC.len() as u64);
+    let header = container::read_header(&mut input).unwrap();
+    assert!(header.rle);
+    let mut out = Vec::new();
+    while let Some(block) = container::read_block_header(&mut input, &header).unwrap() {
+        out.push(block.rle);
+        let extra = if header.block_crc { 4 } else { 0 };
+        input.set_position(input.position() + block.payload_len + extra);
+    }
+    out
+}
+
+#[test]
+fn sequencias_minimas() {
+    let pecas = runs(&[7, 7, 7, 1, 1, 1, 1, 2]);
+    let esperado = [
+        Piece::Symbol(7),
+        Piece::Symbol(7),
+        Piece::Symbol(7),
+        Piece::Symbol(1),
+        Piece::Repeat(3),
+        Piece::Symbol(2),
+    ];
+    assert_eq!(pecas, esperado);
+    for n in [1, 3, 4, 5, 1000] {
+        let data = vec![b'x'; n];
+        let arquivo = Encoder::new(com_rle()).compress_bytes(&data).unwrap();
+        assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+    }
+}
+
+#[test]
+fn bitmap_fica_abaixo_de_1_bit_por_byte() {
+    let data = bitmap();
+    let sem = Encoder::default().compress_bytes(&data).unwrap();
+    let com = Encoder::new(com_rle()).compress_bytes(&data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&com).unwrap(), data);
+    assert!(com.len() * 10 < sem.len(), "{} {}", com.len(), sem.len());
+    assert!(com.len() * 8 * 10 < data.len(), "{}", com.len());
+}
+
+#[test]
+fn cada_bloco_escolhe() {
+    let mut data = bitmap();
+    data.extend(ruido(100_000));
+    let options = Options { block_size: 50_000, ..com_rle() };
+    let arquivo = Encoder::new(options).compress_bytes(&data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data);
+    assert_eq!(marcadores(&arquivo), [true, true, false, false]);
+
+    //onde não ajuda custa só o byte de cada bloco
+    let ruido = ruido(100_000);
+    let sem = Encoder::new(Options { block_size: 50_000, ..Default::default() })
+        .compress_bytes(&ruido)
+        .unwrap();
+    let com = Encoder::new(Options { block_size: 50_000, ..com_rle() })
+        .compress_bytes(&ruido)
+        .unwrap();
+    assert_eq!(com.len(), sem.len() + 2);
+}
+
+#[test]
+fn chars_e_duas_passadas() {
+    let texto = format!("{}ação{}fim", "─".repeat(500), " ".repeat(300)).repeat(20);
+    let options = Options { alphabet: Alphabet::Chars, block_size: 3000, ..com_rle() };
+    let arquivo = Encoder::new(options.clone()).compress_bytes(texto.as_bytes()).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), texto.as_bytes());
+    assert!(marcadores(&arquivo).iter().all(|&rle| rle));
+
+    //com Seek o RLE continua no modo de blocos
+    let mut seekable = Vec::new();
+    Encoder::new(options).compress_seekable(Cursor::new(texto.as_bytes()), &mut seekable).unwrap();
+    assert_eq!(seekable, arquivo);
+}
@@ -0,0 +1,181 @@
+//Huffman generico: os mesmos códigos canônicos sobre u16, um enum com
+//serializer próprio e strings internadas, e tabela estragada vira erro
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+
+use huffman::huffman::{write_varint, Symbol};
+use huffman::symbol::{self, Codebook, FnSerializer, SymbolSerializer, Utf8, Varint};
+use huffman::HuffmanError;
+
+#[test]
+fn leituras_de_sensor_u16() {
+    //ruido em volta de alguns valores, fora da faixa de um byte
+    let leituras: Vec<u16> =
+        (0..20_000u32).map(|i| 1000 + (i.wrapping_mul(2654435761) >> 29) as u16 * 300).collect();
+    let buf = symbol::encode(&leituras, &Varint, 15).unwrap();
+    //8 valores distintos: 3 bits por leitura mais a tabela
+    assert!(buf.len() < leituras.len() * 3 / 8 + 64, "{}", buf.len());
+    assert_eq!(symbol::decode::<u16, _>(&buf, &Varint, u64::MAX).unwrap(), leituras);
+    //sensor parado: um simbolo só, código de tamanho 0
+    let parado = vec![512u16; 100];
+    let buf = symbol::encode(&parado, &Varint, 15).unwrap();
+    assert_eq!(symbol::decode::<u16, _>(&buf, &Varint, u64::MAX).unwrap(), parado);
+    let vazio = symbol::encode::<u16, _>(&[], &Varint, 15).unwrap();
+    assert!(symbol::decode::<u16, _>(&vazio, &Varint, 0).unwrap().is_empty());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Op {
+    Push(u8),
+    Add,
+    Jump(u16),
+    Halt,
+}
+
+//1 byte de tag e o argumento, se houver
+struct OpSerializer;
+
+impl SymbolSerializer<Op> for OpSerializer {
+    fn write_symbol(&self, op: &Op, out: &mut Vec<u8>) {
+        match *op {
+            Op::Push(v) => out.extend([0, v]),
+            Op::Add => out.push(1),
+            Op::Jump(alvo) => {
+                out.push(2);
+                out.extend(alvo.to_be_bytes());
+            }
+            Op::Halt => out.push(3),
+        }
+    }
+
+    fn read_symbol<R: Read + ?Sized>(&self, input: &mut R) -> Result<Op, HuffmanError> {
+        let mut tag = [0u8; 1];
+        input.read_exact(&mut tag)?;
+        Ok(match tag[0] {
+            0 => {
+                input.read_exact(&mut tag)?;
+                Op::Push(tag[0])
+            }
+            1 => Op::Add,
+            2 => {
+                let mut alvo = [0u8; 2];
+                input.read_exact(&mut alvo)?;
+                Op::Jump(u16::from_be_bytes(alvo))
+            }
+            3 => Op::Halt,
+            _ => return Err(HuffmanError::Corrupt("opcode desconhecido")),
+        })
+    }
+}
+
+fn programa() -> Vec<Op> {
+    let mut ops = Vec::new();
+    for i in 0..500u16 {
+        ops.extend([Op::Push((i % 4) as u8), Op::Push(1), Op::Add]);
+        if i % 10 == 0 {
+            ops.push(Op::Jump(i / 10));
+        }
+    }
+    ops.push(Op::Halt);
+    ops
+}
+
+#[test]
+fn opcodes_com_serializer_proprio() {
+    let ops = programa();
+    let buf = symbol::encode(&ops, &OpSerializer, 15).unwrap();
+    assert_eq!(symbol::decode(&buf, &OpSerializer, 1 << 20).unwrap(), ops);
+
+    //o mesmo formato com um FnSerializer
+    let por_funcoes = FnSerializer::new(
+        |op: &Op, out: &mut Vec<u8>| OpSerializer.write_symbol(op, out),
+        |input: &mut dyn Read| OpSerializer.read_symbol(input),
+    );
+    assert_eq!(symbol::encode(&ops, &por_funcoes, 15).unwrap(), buf);
+    assert_eq!(symbol::decode(&buf, &por_funcoes, 1 << 20).unwrap(), ops);
+}
+
+#[test]
+fn strings_internadas() {
+    let nomes: Vec<Arc<str>> =
+        ["GET", "POST", "PUT", "DELETE", "ação"].into_iter().map(Arc::from).collect();
+    let requisicoes: Vec<Arc<str>> =
+        (0..3_000usize).map(|i| nomes[(i * i) % 7 % nomes.len()].clone()).collect();
+    let buf = symbol::encode(&requisicoes, &Utf8, 15).unwrap();
+    assert_eq!(symbol::decode::<Arc<str>, _>(&buf, &Utf8, 3_000).unwrap(), requisicoes);
+    //String usa o mesmo formato
+    let strings: Vec<String> = requisicoes.iter().map(|s| s.to_string()).collect();
+    assert_eq!(symbol::encode(&strings, &Utf8, 15).unwrap(), buf);
+}
+
+fn tabela<S: Symbol>(freqs: &[(S, u64)]) -> Codebook<S> {
+    let freqs: HashMap<S, u64> = freqs.iter().cloned().collect();
+    Codebook::from_frequencies(&freqs, 15)
+}
+
+#[test]
+fn tabela_estragada_vira_erro() {
+    let codebook = tabela(&[(10u16, 5), (20, 3), (30, 1), (40, 1)]);
+    assert_eq!(codebook.code(&10), Some((0, 1)));
+    assert_eq!(codebook.code(&99), None);
+    let mut buf = Vec::new();
+    codebook.write_table(&Varint, &mut buf);
+    let lida = Codebook::<u16>::read_table(&Varint, &mut buf.as_slice()).unwrap();
+    assert_eq!(lida.lengths(), codebook.lengths());
+
+    //tamanho que deixa o código incompleto
+    let mut errado = buf.clone();
+    errado[2] = 2;
+    assert!(matches!(
+        Codebook::<u16>::read_table(&Varint, &mut errado.as_slice()),
+        Err(HuffmanError::MalformedTree(_))
+    ));
+    //simbolos fora de ordem
+    let mut errado = buf.clone();
+    errado[3] = 5;
+    assert!(matches!(
+        Codebook::<u16>::read_table(&Varint, &mut errado.as_slice()),
+        Err(HuffmanError::MalformedTree(_))
+    ));
+    //simbolo que não cabe no tipo
+    assert!(Codebook::<u8>::read_table(&Varint, &mut [1u8, 0x80, 0x02, 0].as_slice()).is_err());
+    //tabela cortada
+    assert!(Codebook::<u16>::read_table(&Varint, &mut &buf[..buf.len() - 1]).is_err());
+    //simbolo fora da tabela no encode
+    let mut bits = huffman::bits::BitWriter::new(Vec::new());
+    assert!(codebook.encode(&[10, 99], &mut bits).is_err());
+}
+
+//a tabela seguida de uma quantidade de simbolos escolhida a mão
+fn com_quantidade(codebook: &Codebook<u16>, n: u64, payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    codebook.write_table(&Varint, &mut buf);
+    write_varint(n, &mut buf);
+    buf.extend_from_slice(payload);
+    buf
+}
+
+#[test]
+fn quantidade_de_simbolos_limitada() {
+    //todos os códigos com 1 bit ou mais: cada simbolo gasta pelo menos um bit
+    let codebook = tabela(&[(1u16, 3), (2, 1)]);
+    let buf = |n| com_quantidade(&codebook, n, &[0u8; 4]);
+    assert_eq!(symbol::decode::<u16, _>(&buf(32), &Varint, u64::MAX).unwrap(), vec![1; 32]);
+    for n in [33, 1 << 40, u64::MAX] {
+        let lido = symbol::decode::<u16, _>(&buf(n), &Varint, u64::MAX);
+        assert!(matches!(lido, Err(HuffmanError::Corrupt(_))), "{}", n);
+    }
+    assert!(symbol::decode::<u16, _>(&buf(32), &Varint, 31).is_err());
+
+    //um simbolo só, código de tamanho 0: o payload não limita nada, só o
+    //max_symbols
+    let codebook = tabela(&[(7u16, 1)]);
+    assert_eq!(codebook.lengths(), [(7, 0)]);
+    let buf = |n| com_quantidade(&codebook, n, &[]);
+    assert_eq!(symbol::decode::<u16, _>(&buf(1_000), &Varint, 1_000).unwrap(), vec![7; 1_000]);
+    for n in [1_001, 1 << 40, u64::MAX] {
+        let lido = symbol::decode::<u16, _>(&buf(n), &Varint, 1_000);
+        assert!(matches!(lido, Err(HuffmanError::Corrupt(_))), "{}", n);
+    }
+}
@@ -0,0 +1,101 @@
+//alfabeto de tokens: quebra em palavras e separadores, ida e volta com
+//qualquer entrada e ganho sobre o Huffman de bytes em texto
+use std::sync::Arc;
+
+use huffman::tokens::split;
+use huffman::{Decoder, Encoder, Method, Options};
+
+//texto com um vocabulario pequeno e frequencias bem desiguais
+fn texto() -> Vec<u8> {
+    let palavras = [
+        "de", "que", "não", "para", "uma", "com", "arquivo", "código", "bloco", "tabela",
+        "simbolo", "frequencia", "Huffman", "compactação", "o", "a", "e", "é", "se", "mais",
+    ];
+    let mut x = 7u32;
+    let mut out = String::new();
+    for i in 0..40_000 {
+        x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        let r = ((x >> 16) % 400) as usize;
+        out.push_str(palavras[(r * r / 8000).min(palavras.len() - 1)]);
+        out.push_str(match i % 17 {
+            16 => ".\n",
+            7 => ", ",
+            _ => " ",
+        });
+    }
+    out.into_bytes()
+}
+
+#[test]
+fn quebra_em_palavras_e_separadores() {
+    let pedacos = split("olá, mundo!\n  x1".as_bytes(), None);
+    let esperado: Vec<&[u8]> =
+        vec!["olá".as_bytes(), b", ", b"mundo", b"!\n  ", b"x1"];
+    assert_eq!(pedacos, esperado);
+
+    let lista = vec![b"ab".to_vec(), b"abc".to_vec(), b"\r\n".to_vec()];
+    let pedacos = split(b"abcabx\r\n", Some(&lista));
+    let esperado: Vec<&[u8]> = vec![b"abc", b"ab", b"x", b"\r\n"];
+    assert_eq!(pedacos, esperado);
+}
+
+#[test]
+fn ganha_do_huffman_de_bytes_em_texto() {
+    let data = texto();
+    let bytes = Encoder::default().compress_bytes(&data).unwrap();
+    let options = Options { method: Method::Tokens, ..Default::default() };
+    let tokens = Encoder::new(options).compress_bytes(&data).unwrap();
+    assert_eq!(Decoder::new().decompress_bytes(&tokens).unwrap(), data);
+    assert!(tokens.len() * 3 < bytes.len() * 2, "{} {}", tokens.len(), bytes.len());
+}
+
+#[test]
+fn tokens_do_usuario_e_binario_fazem_ida_e_volta() {
+    let lista = Arc::new(vec![b"GET /".to_vec(), b" HTTP/1.1\r\n".to_vec(), b"200".to_vec()]);
+    let mut data = b"GET /a HTTP/1.1\r\n200 GET /b HTTP/1.1\r\n".repeat(300);
+    data.extend((0..5000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8));
+    for options in [
+        Options { method: Method::Tokens, tokens: Some(lista), ..Default::default() },
+        Options { method: Method::Tokens, block_size: 777, threads: 3, ..Default::default() },
+        Options { method: Method::Tokens, max_code_len: 8, ..Default::default() },
+    ] {
+        let arquivo = Encoder::new(options.clone()).compress_bytes(&data).unwrap();
+        assert_eq!(Decoder::new().decompress_bytes(&arquivo).unwrap(), data, "{:?}", options);
+    }
+}
+
+//o maior token da lista em cada posição, testando a lista inteira
+fn split_ingenuo<'a>(data: &'a [u8], lista: &[Vec<u8>]) -> Vec<&'a [u8]> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let len = lista
+            .iter()
+            .filter(|t| t.len() >= 2 && data[i..].starts_with(t))
+            .map(Vec::len)
+            .max()
+            .unwrap_or(1);
+        out.push(&data[i..i + len]);
+        i += len;
+    }
+    out
+}
+
+#[test]
+fn vocabulario_grande_com_prefixos_em_comum() {
+    //tokens que são prefixos uns dos outros, de 1 a 40 bytes, sobre um
+    //alfabeto pequeno para eles aparecerem na entrada
+    let mut x = 99u32;
+    let mut proximo = move || {
+        x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        x >> 16
+    };
+    let mut lista: Vec<Vec<u8>> = (0..1_000)
+        .map(|_| (0..1 + proximo() % 40).map(|_| b'a' + (proximo() % 3) as u8).collect())
+        .collect();
+    lista.push(Vec::new());
+    let data: Vec<u8> = (0..20_000).map(|_| b'a' + (proximo() % 4) as u8).collect();
+    let pedacos = split(&data, Some(&lista));
+    assert_eq!(pedacos, split_ingenuo(&data, &lista));
+    assert!(pedacos.iter().any(|p| p.len() > 5));
+}